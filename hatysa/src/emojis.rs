@@ -0,0 +1,48 @@
+//! The custom emojis in each of the bot's guilds.
+//!
+//! Discord sends every guild's emojis when the bot connects and whenever they
+//! change, so keeping track of them in the client's [`TypeMap`][typemap] means
+//! that custom emojis can be checked without making any requests.
+//!
+//! [typemap]: serenity::prelude::TypeMap
+
+use serenity::{
+    model::id::{EmojiId, GuildId},
+    prelude::TypeMapKey,
+};
+use tokio::sync::RwLock;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// The custom emojis in each guild.
+#[derive(Debug, Default)]
+pub struct Emojis {
+    /// The IDs of the emojis in each guild.
+    guilds: HashMap<GuildId, HashSet<EmojiId>>,
+}
+
+impl TypeMapKey for Emojis {
+    type Value = Arc<RwLock<Emojis>>;
+}
+
+impl Emojis {
+    /// Replace the emojis in a guild.
+    pub fn set(&mut self, guild_id: GuildId, emojis: impl IntoIterator<Item = EmojiId>) {
+        self.guilds.insert(guild_id, emojis.into_iter().collect());
+    }
+
+    /// Forget about a guild, once the bot is no longer in it.
+    pub fn remove(&mut self, guild_id: GuildId) {
+        self.guilds.remove(&guild_id);
+    }
+
+    /// Determine whether an emoji belongs to any of the guilds.
+    pub fn contains(&self, emoji_id: EmojiId) -> bool {
+        self.guilds
+            .values()
+            .any(|emojis| emojis.contains(&emoji_id))
+    }
+}
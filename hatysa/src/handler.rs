@@ -22,13 +22,28 @@ use chrono::{DateTime, Utc};
 use serenity::{
     async_trait,
     client::{Context, EventHandler},
-    model::{channel::Message, gateway::Activity, gateway::Ready},
+    model::{
+        channel::Message,
+        gateway::Activity,
+        gateway::Ready,
+        guild::{Emoji, Guild, UnavailableGuild},
+        id::{EmojiId, GuildId},
+    },
 };
 use tracing::{Instrument, Level};
 
-use iota_orionis::command::Command;
+use std::{collections::HashMap, str::FromStr};
 
-use crate::task::Task;
+use iota_orionis::command::{
+    Command, CommandError, Direction, Encoding, Font, Granularity, Intensity, Style, DEFAULT_COW,
+    DEFAULT_SEPARATOR, DEFAULT_WIDTH, MAX_MESSAGE_LENGTH,
+};
+
+use crate::{emojis::Emojis, keywords::Keywords, task::Task};
+
+/// The names of the [`Command::Overlay`] commands. Longer names come first, so
+/// that `strikethrough` isn't read as `strike` followed by `through`.
+const OVERLAYS: &[&str] = &["overline", "slash", "strikethrough", "strike", "underline"];
 
 /// Hatysa event handler.
 ///
@@ -54,17 +69,51 @@ impl EventHandler for Handler {
             .await;
     }
 
+    async fn guild_create(&self, ctx: Context, guild: Guild) {
+        if let Some(emojis) = ctx.data.read().await.get::<Emojis>() {
+            emojis.write().await.set(guild.id, guild.emojis.into_keys());
+        }
+    }
+
+    async fn guild_emojis_update(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        current_state: HashMap<EmojiId, Emoji>,
+    ) {
+        if let Some(emojis) = ctx.data.read().await.get::<Emojis>() {
+            emojis
+                .write()
+                .await
+                .set(guild_id, current_state.into_keys());
+        }
+    }
+
+    async fn guild_delete(&self, ctx: Context, incomplete: UnavailableGuild) {
+        // Guilds also get "deleted" during outages, but they come back with
+        // the same emojis afterwards.
+        if incomplete.unavailable {
+            return;
+        }
+
+        if let Some(emojis) = ctx.data.read().await.get::<Emojis>() {
+            emojis.write().await.remove(incomplete.id);
+        }
+    }
+
     async fn message(&self, ctx: Context, msg: Message) {
         let span = trace_span!("handler");
         async move {
-            if let Some(command) = self.interpret_command(&msg).await {
+            if let Some(command) = self.interpret_command(&ctx, &msg).await {
                 event!(
                     Level::DEBUG,
                     id = msg.id.0,
                     "message is a command, executing",
                 );
 
-                Task::new(command, ctx, msg).execute().await;
+                Task::new(command, ctx, msg, self.prefix.clone())
+                    .execute()
+                    .await;
             } else {
                 event!(Level::DEBUG, id = msg.id.0, "message is not a command");
             }
@@ -76,10 +125,24 @@ impl EventHandler for Handler {
 
 impl Handler {
     /// Attempt to parse a message as a command. If the message does not contain
-    /// a valid command, `None` is returned.
-    async fn interpret_command(&self, msg: &Message) -> Option<Command> {
+    /// a command, `None` is returned, and if it contains a command with invalid
+    /// arguments, the error is returned so that it can be reported.
+    async fn interpret_command(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+    ) -> Option<Result<Command, CommandError>> {
         debug!("interpreting command");
 
+        // Substitutions are written the same way as in sed, so they work with
+        // or without a prefix. The bot's own messages are ignored, in case a
+        // correction happens to look like another substitution.
+        if !msg.author.bot {
+            if let Some(parts) = split_substitution(&msg.content, &self.prefix) {
+                return Some(Ok(parse_substitute(parts)));
+            }
+        }
+
         // Non-private messages must have a prefix on them, but it's optional
         // for private messages, so if we don't find a prefix, check if it was a
         // private message and allow it if it was.
@@ -92,42 +155,601 @@ impl Handler {
         });
 
         if let Some(tail) = tail {
-            if let Some(tail) = tail.strip_prefix("clap").map(|tail| tail.trim()) {
-                Some(Command::Clap {
+            if let Some(tail) = tail.strip_prefix("atbash").map(|tail| tail.trim()) {
+                Some(Ok(Command::Atbash {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail.strip_prefix("banner").map(|tail| tail.trim()) {
+                Some(parse_banner(tail))
+            } else if let Some(tail) = tail.strip_prefix("braille").map(|tail| tail.trim()) {
+                Some(Ok(Command::Braille {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail.strip_prefix("caesar").map(|tail| tail.trim()) {
+                Some(parse_caesar(tail))
+            } else if let Some(tail) = tail.strip_prefix("charinfo").map(|tail| tail.trim()) {
+                Some(Ok(Command::CharInfo {
                     input: tail.to_string(),
-                })
+                }))
+            } else if let Some(tail) = tail.strip_prefix("clap").map(|tail| tail.trim()) {
+                Some(parse_clap(tail))
+            } else if let Some(tail) = tail.strip_prefix("cowsay").map(|tail| tail.trim()) {
+                Some(parse_cowsay(tail, false))
+            } else if let Some(tail) = tail.strip_prefix("cowthink").map(|tail| tail.trim()) {
+                Some(parse_cowsay(tail, true))
+            } else if let Some((dialect, tail)) = ["cowboy", "pirate", "shakespeare"]
+                .iter()
+                .find_map(|&name| tail.strip_prefix(name).map(|tail| (name, tail.trim())))
+            {
+                Some(dialect.parse().map(|dialect| Command::Dialect {
+                    input: tail.to_string(),
+                    dialect,
+                }))
+            } else if let Some(tail) = tail.strip_prefix("decode").map(|tail| tail.trim()) {
+                Some(
+                    parse_encoding(tail)
+                        .map(|(input, encoding)| Command::Decode { input, encoding }),
+                )
+            } else if let Some(tail) = tail.strip_prefix("drunk").map(|tail| tail.trim()) {
+                Some(parse_drunk(tail))
+            } else if let Some(tail) = tail.strip_prefix("emojify").map(|tail| tail.trim()) {
+                Some(Ok(Command::Emojify {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail.strip_prefix("emojipasta").map(|tail| tail.trim()) {
+                Some(parse_emojipasta(tail, guild_keywords(ctx, msg).await))
+            } else if let Some(tail) = tail.strip_prefix("encode").map(|tail| tail.trim()) {
+                Some(
+                    parse_encoding(tail)
+                        .map(|(input, encoding)| Command::Encode { input, encoding }),
+                )
+            } else if let Some(tail) = tail.strip_prefix("fancy").map(|tail| tail.trim()) {
+                Some(parse_fancy(tail))
+            } else if let Some(tail) = tail.strip_prefix("flip").map(|tail| tail.trim()) {
+                Some(Ok(Command::Flip {
+                    input: tail.to_string(),
+                }))
             } else if tail.starts_with("info") {
-                Some(Command::Info {
+                Some(Ok(Command::Info {
                     start_time: self.start_time,
-                })
+                }))
+            } else if let Some(tail) = tail.strip_prefix("leet").map(|tail| tail.trim()) {
+                Some(parse_leet(tail))
+            } else if let Some(tail) = tail.strip_prefix("mirror").map(|tail| tail.trim()) {
+                Some(Ok(Command::Mirror {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail.strip_prefix("morse").map(|tail| tail.trim()) {
+                Some(Ok(Command::Morse {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail
+                .strip_prefix("normalize")
+                .or_else(|| tail.strip_prefix("unzalgo"))
+                .map(|tail| tail.trim())
+            {
+                Some(Ok(Command::Normalize {
+                    input: tail.to_string(),
+                }))
+            } else if let Some((overlay, tail)) = OVERLAYS
+                .iter()
+                .find_map(|&name| tail.strip_prefix(name).map(|tail| (name, tail.trim())))
+            {
+                Some(overlay.parse().map(|overlay| Command::Overlay {
+                    input: tail.to_string(),
+                    overlay,
+                }))
             } else if tail.starts_with("ping") {
-                Some(Command::Ping)
+                Some(Ok(Command::Ping))
             } else if let Some(tail) = tail.strip_prefix("react").map(|tail| tail.trim()) {
-                Some(Command::React {
+                Some(Ok(Command::React {
                     input: tail.to_owned(),
-                })
+                }))
+            } else if let Some(tail) = tail.strip_prefix("rot13").map(|tail| tail.trim()) {
+                Some(Ok(Command::Rot13 {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail.strip_prefix("scramble").map(|tail| tail.trim()) {
+                Some(parse_scramble(tail))
             } else if let Some(tail) = tail.strip_prefix("sketchify").map(|tail| tail.trim()) {
-                Some(Command::Sketchify {
+                Some(Ok(Command::Sketchify {
                     url_raw: tail.to_owned(),
-                })
+                }))
+            } else if let Some(tail) = tail.strip_prefix("smallcaps").map(|tail| tail.trim()) {
+                Some(Ok(Command::SmallCaps {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail.strip_prefix("spoiler").map(|tail| tail.trim()) {
+                Some(parse_spoiler(tail))
             } else if let Some(tail) = tail.strip_prefix("spongebob").map(|tail| tail.trim()) {
-                Some(Command::Spongebob {
+                Some(parse_spongebob(tail))
+            } else if let Some(tail) = tail.strip_prefix("subscript").map(|tail| tail.trim()) {
+                Some(Ok(Command::Subscript {
                     input: tail.to_string(),
-                })
+                }))
+            } else if let Some(tail) = tail.strip_prefix("superscript").map(|tail| tail.trim()) {
+                Some(Ok(Command::Superscript {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail.strip_prefix("unleet").map(|tail| tail.trim()) {
+                Some(Ok(Command::Unleet {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail.strip_prefix("unmorse").map(|tail| tail.trim()) {
+                Some(Ok(Command::Unmorse {
+                    input: tail.to_string(),
+                }))
+            } else if let Some(tail) = tail.strip_prefix("uwu").map(|tail| tail.trim()) {
+                Some(parse_uwu(tail))
+            } else if let Some(tail) = tail.strip_prefix("vigenere").map(|tail| tail.trim()) {
+                Some(parse_vigenere(tail))
             } else if let Some(tail) = tail.strip_prefix("wavy").map(|tail| tail.trim()) {
-                Some(Command::Wavy {
+                Some(Ok(Command::Wavy {
                     input: tail.to_string(),
-                })
+                }))
             } else {
                 tail.strip_prefix("zalgo")
                     .map(|tail| tail.trim())
-                    .map(|tail| Command::Zalgo {
-                        input: tail.to_string(),
-                        max_chars: None,
-                    })
+                    .map(parse_zalgo)
             }
         } else {
             None
         }
     }
 }
+
+/// Parse the options and input text of a [`Command::Uwu`], in the form
+/// `[--intensity <intensity>] [--seed <seed>] <input>`.
+fn parse_uwu(tail: &str) -> Result<Command, CommandError> {
+    let (options, input) = parse_options(tail, &["intensity", "seed"])?;
+
+    Ok(Command::Uwu {
+        input: input.to_string(),
+        intensity: options.intensity,
+        seed: options.seed,
+    })
+}
+
+/// Parse the key, operation and input text of a [`Command::Vigenere`], in the
+/// form `<key> encode|decode <input>`.
+fn parse_vigenere(tail: &str) -> Result<Command, CommandError> {
+    let (key, rest) = tail.split_once(char::is_whitespace).unwrap_or((tail, ""));
+    let rest = rest.trim_start();
+    let (operation, input) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+    Ok(Command::Vigenere {
+        input: input.trim_start().to_string(),
+        key: key.to_string(),
+        operation: operation.parse()?,
+    })
+}
+
+/// Parse the options and input text of a [`Command::Zalgo`], in the form
+/// `[--intensity <intensity>] [--direction <direction>,...] [--seed <seed>]
+/// <input>`.
+fn parse_zalgo(tail: &str) -> Result<Command, CommandError> {
+    let (options, input) = parse_options(tail, &["intensity", "direction", "seed"])?;
+
+    Ok(Command::Zalgo {
+        input: input.to_string(),
+        max_chars: Some(MAX_MESSAGE_LENGTH),
+        intensity: options.intensity,
+        directions: options.directions,
+        seed: options.seed,
+    })
+}
+
+/// Parse the options and input text of a [`Command::Banner`], in the form
+/// `[--font <font>] <input>`.
+fn parse_banner(tail: &str) -> Result<Command, CommandError> {
+    let (font, input) = match take_option(tail, "font") {
+        Some((font, rest)) => (parse_option("font", font)?, rest),
+        None => (Font::default(), tail),
+    };
+
+    Ok(Command::Banner {
+        input: input.to_string(),
+        font,
+    })
+}
+
+/// Parse the shift and input text of a [`Command::Caesar`], in the form
+/// `<shift> <input>`, or a [`Command::CaesarBruteforce`], in the form
+/// `bruteforce <input>`.
+fn parse_caesar(tail: &str) -> Result<Command, CommandError> {
+    let (first, rest) = tail.split_once(char::is_whitespace).unwrap_or((tail, ""));
+    let input = rest.trim_start().to_string();
+
+    if first == "bruteforce" {
+        return Ok(Command::CaesarBruteforce { input });
+    }
+
+    let shift = first.parse().map_err(|_| CommandError::InvalidArgument {
+        argument: "shift".to_string(),
+        value: first.to_string(),
+    })?;
+
+    Ok(Command::Caesar { input, shift })
+}
+
+/// Parse the options and input text of a [`Command::Clap`], in the form
+/// `[--with <emoji>] [--no-trailing] <input>`.
+fn parse_clap(mut tail: &str) -> Result<Command, CommandError> {
+    let mut separator = DEFAULT_SEPARATOR.to_string();
+    let mut trailing = true;
+
+    loop {
+        if let Some((value, rest)) = take_option(tail, "with") {
+            separator = value.to_string();
+            tail = rest;
+        } else if let Some(rest) = take_flag(tail, "no-trailing") {
+            trailing = false;
+            tail = rest;
+        } else {
+            break;
+        }
+    }
+
+    Ok(Command::Clap {
+        input: tail.to_string(),
+        separator,
+        trailing,
+    })
+}
+
+/// Parse the options and input text of a [`Command::Cowsay`], in the form
+/// `[--cow <cow>] [--width <width>] <input>`.
+fn parse_cowsay(mut tail: &str, think: bool) -> Result<Command, CommandError> {
+    let mut cow = DEFAULT_COW.to_string();
+    let mut width = DEFAULT_WIDTH;
+
+    loop {
+        if let Some((value, rest)) = take_option(tail, "cow") {
+            cow = value.to_string();
+            tail = rest;
+        } else if let Some((value, rest)) = take_option(tail, "width") {
+            width = parse_option("width", value)?;
+            tail = rest;
+        } else {
+            break;
+        }
+    }
+
+    Ok(Command::Cowsay {
+        input: tail.to_string(),
+        cow,
+        width,
+        think,
+    })
+}
+
+/// Parse the options and input text of a [`Command::Drunk`], in the form
+/// `[--intensity <intensity>] [--seed <seed>] <input>`.
+fn parse_drunk(tail: &str) -> Result<Command, CommandError> {
+    let (options, input) = parse_options(tail, &["intensity", "seed"])?;
+
+    Ok(Command::Drunk {
+        input: input.to_string(),
+        intensity: options.intensity,
+        seed: options.seed,
+    })
+}
+
+/// Parse a [`Command::Emojipasta`], in the form `[--seed <seed>] <input>`, or
+/// one of the commands to manage its extra keywords, which are given as flags
+/// so that they can't be mistaken for input: `--add <keyword> <emoji>`,
+/// `--remove <keyword>` or `--keywords`.
+fn parse_emojipasta(tail: &str, extra: Vec<(String, String)>) -> Result<Command, CommandError> {
+    if let Some(rest) = take_flag(tail, "add") {
+        let (keyword, emoji) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+        Ok(Command::EmojipastaAdd {
+            keyword: keyword.to_string(),
+            emoji: emoji.trim().to_string(),
+        })
+    } else if let Some(rest) = take_flag(tail, "remove") {
+        Ok(Command::EmojipastaRemove {
+            keyword: rest.to_string(),
+        })
+    } else if take_flag(tail, "keywords").is_some() {
+        Ok(Command::EmojipastaKeywords { keywords: extra })
+    } else {
+        let (options, input) = parse_options(tail, &["seed"])?;
+
+        Ok(Command::Emojipasta {
+            input: input.to_string(),
+            seed: options.seed,
+            extra,
+        })
+    }
+}
+
+/// Get the extra emojipasta keywords for the guild the message was sent in.
+async fn guild_keywords(ctx: &Context, msg: &Message) -> Vec<(String, String)> {
+    let data = ctx.data.read().await;
+
+    match (msg.guild_id, data.get::<Keywords>()) {
+        (Some(guild_id), Some(keywords)) => keywords.read().await.get(guild_id),
+        _ => Vec::new(),
+    }
+}
+
+/// Parse the encoding and input text of a [`Command::Encode`] or
+/// [`Command::Decode`], in the form `<encoding> <input>`.
+fn parse_encoding(tail: &str) -> Result<(String, Encoding), CommandError> {
+    let (encoding, input) = tail.split_once(char::is_whitespace).unwrap_or((tail, ""));
+
+    Ok((input.trim_start().to_string(), encoding.parse()?))
+}
+
+/// Parse the style and input text of a [`Command::Fancy`], in the form
+/// `<style> <input>`.
+fn parse_fancy(tail: &str) -> Result<Command, CommandError> {
+    let (style, input) = tail.split_once(char::is_whitespace).unwrap_or((tail, ""));
+
+    Ok(Command::Fancy {
+        input: input.trim_start().to_string(),
+        style: style.parse::<Style>()?,
+    })
+}
+
+/// Parse the level and input text of a [`Command::Leet`], in the form
+/// `[<level>] <input>`. If the first word isn't a level from 1 to 3, it's
+/// treated as part of the input and the default level of 1 is used, so that
+/// text like `4 score` can be converted.
+fn parse_leet(tail: &str) -> Result<Command, CommandError> {
+    let (first, rest) = tail.split_once(char::is_whitespace).unwrap_or((tail, ""));
+
+    let (level, input) = match first.parse::<u8>() {
+        Ok(level @ 1..=3) if first.len() == 1 => (level, rest.trim_start()),
+        _ => (1, tail),
+    };
+
+    Ok(Command::Leet {
+        input: input.to_string(),
+        level,
+    })
+}
+
+/// Parse the options and input text of a [`Command::Scramble`], in the form
+/// `[--seed <seed>] <input>`.
+fn parse_scramble(tail: &str) -> Result<Command, CommandError> {
+    let (options, input) = parse_options(tail, &["seed"])?;
+
+    Ok(Command::Scramble {
+        input: input.to_string(),
+        seed: options.seed,
+    })
+}
+
+/// Parse the granularity and input text of a [`Command::Spoiler`], in the form
+/// `[word|char] <input>`. The granularity is only taken if text follows it, so
+/// `,spoiler word` on its own hides the text "word".
+fn parse_spoiler(tail: &str) -> Result<Command, CommandError> {
+    let (granularity, input) = match tail.split_once(char::is_whitespace) {
+        Some((first, rest)) => match first.parse() {
+            Ok(granularity) => (granularity, rest.trim_start()),
+            Err(_) => (Granularity::default(), tail),
+        },
+        None => (Granularity::default(), tail),
+    };
+
+    Ok(Command::Spoiler {
+        input: input.to_string(),
+        granularity,
+    })
+}
+
+/// Split a sed-style substitution, in the form
+/// `s/<pattern>/<replacement>/[<flags>]`, into its pattern, replacement and
+/// flags, which may be preceded by the prefix. A `/` can be included in the
+/// pattern or replacement by escaping it as `\/`.
+///
+/// The closing `/` is required and only `g` and `i` can follow it, so that
+/// ordinary messages starting with `s/` aren't mistaken for substitutions. If
+/// the content isn't a substitution, `None` is returned.
+pub(crate) fn split_substitution(content: &str, prefix: &str) -> Option<(String, String, String)> {
+    let content = content.trim();
+    let content = content.strip_prefix(prefix).unwrap_or(content);
+
+    let mut parts = vec![String::new()];
+    let mut chars = content.strip_prefix("s/")?.chars();
+
+    while let Some(c) = chars.next() {
+        let part = parts.last_mut()?;
+
+        match c {
+            '/' => parts.push(String::new()),
+            // Keep other escapes as they are, so that the pattern and
+            // replacement can interpret them.
+            '\\' => match chars.next() {
+                Some('/') => part.push('/'),
+                Some(next) => {
+                    part.push(c);
+                    part.push(next);
+                }
+                None => part.push(c),
+            },
+            _ => part.push(c),
+        }
+    }
+
+    let mut parts = parts.into_iter();
+
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(pattern), Some(replacement), Some(flags), None)
+            if !pattern.is_empty() && flags.chars().all(|c| matches!(c, 'g' | 'i')) =>
+        {
+            Some((pattern, replacement, flags))
+        }
+        _ => None,
+    }
+}
+
+/// Parse the flags of a [`Command::Substitute`], which can be `g` to replace
+/// every match and `i` to ignore case.
+///
+/// The input is left empty, and filled in with the message to correct when the
+/// task runs.
+fn parse_substitute((pattern, replacement, flags): (String, String, String)) -> Command {
+    Command::Substitute {
+        input: String::new(),
+        pattern,
+        replacement,
+        global: flags.contains('g'),
+        ignore_case: flags.contains('i'),
+    }
+}
+
+/// Parse the options and input text of a [`Command::Spongebob`], in the form
+/// `[--random] [--seed <seed>] <input>`.
+fn parse_spongebob(tail: &str) -> Result<Command, CommandError> {
+    let (options, input) = parse_options(tail, &["random", "seed"])?;
+
+    Ok(Command::Spongebob {
+        input: input.to_string(),
+        random: options.random,
+        seed: options.seed,
+    })
+}
+
+/// The options shared by the commands with random output.
+#[derive(Default)]
+struct Options {
+    /// The value of `--intensity`.
+    intensity: Intensity,
+    /// The values of `--direction`, which can each be a comma-separated list.
+    directions: Vec<Direction>,
+    /// Whether `--random` was given.
+    random: bool,
+    /// The value of `--seed`.
+    seed: Option<u64>,
+}
+
+/// Parse the options at the start of the input, in any order, accepting only
+/// those with the given names. Returns the options and the rest of the input.
+fn parse_options<'a>(
+    mut tail: &'a str,
+    names: &[&str],
+) -> Result<(Options, &'a str), CommandError> {
+    let mut options = Options::default();
+
+    loop {
+        let option = move |name| {
+            names
+                .contains(&name)
+                .then(|| take_option(tail, name))
+                .flatten()
+        };
+
+        if let Some((value, rest)) = option("intensity") {
+            options.intensity = parse_option("intensity", value)?;
+            tail = rest;
+        } else if let Some((value, rest)) = option("direction") {
+            for direction in value.split(',') {
+                options
+                    .directions
+                    .push(parse_option("direction", direction)?);
+            }
+            tail = rest;
+        } else if let Some(rest) = take_flag(tail, "random").filter(|_| names.contains(&"random")) {
+            options.random = true;
+            tail = rest;
+        } else if let Some((value, rest)) = option("seed") {
+            options.seed = Some(parse_option("seed", value)?);
+            tail = rest;
+        } else {
+            break;
+        }
+    }
+
+    Ok((options, tail))
+}
+
+/// Attempt to take a flag in the form `--name` from the start of the input,
+/// returning the rest of the input if successful.
+fn take_flag<'a>(input: &'a str, name: &str) -> Option<&'a str> {
+    let rest = input.strip_prefix("--")?.strip_prefix(name)?;
+
+    // As with options, the name must be followed by whitespace (or nothing).
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    Some(rest.trim_start())
+}
+
+/// Attempt to take an option in the form `--name value` from the start of the
+/// input, returning its value and the rest of the input if successful.
+fn take_option<'a>(input: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+    let rest = input.strip_prefix("--")?.strip_prefix(name)?;
+
+    // The name must be followed by whitespace, so that `--seed` doesn't match
+    // `--seedless`, for example.
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let rest = rest.trim_start();
+    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+
+    Some((&rest[..end], rest[end..].trim_start()))
+}
+
+/// Parse the value of an option, reporting an invalid value against the option
+/// as it's written, like `--seed`.
+fn parse_option<T: FromStr>(name: &str, value: &str) -> Result<T, CommandError> {
+    value.parse().map_err(|_| CommandError::InvalidArgument {
+        argument: format!("--{}", name),
+        value: value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(pattern: &str, replacement: &str, flags: &str) -> Option<(String, String, String)> {
+        Some((
+            pattern.to_string(),
+            replacement.to_string(),
+            flags.to_string(),
+        ))
+    }
+
+    #[test]
+    fn split_substitution_with_and_without_prefix() {
+        assert_eq!(
+            split_substitution("s/teh/the/", ","),
+            parts("teh", "the", "")
+        );
+        assert_eq!(
+            split_substitution(",s/teh/the/", ","),
+            parts("teh", "the", "")
+        );
+    }
+
+    #[test]
+    fn split_substitution_keeps_flags_and_escapes() {
+        assert_eq!(
+            split_substitution("s/a\\/b/c/gi", ","),
+            parts("a/b", "c", "gi")
+        );
+        assert_eq!(
+            split_substitution("s/\\d+/n/g", ","),
+            parts("\\d+", "n", "g")
+        );
+    }
+
+    #[test]
+    fn split_substitution_requires_closing_slash() {
+        assert_eq!(split_substitution("s/o to x / y", ","), None);
+        assert_eq!(split_substitution("s/teh/the", ","), None);
+    }
+
+    #[test]
+    fn split_substitution_ignores_text_after_closing_slash() {
+        assert_eq!(split_substitution("s/teh/the/ oops", ","), None);
+        assert_eq!(split_substitution("s/teh/the/x", ","), None);
+    }
+}
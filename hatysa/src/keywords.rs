@@ -0,0 +1,139 @@
+//! Extra emojipasta keywords added by guild admins.
+//!
+//! The keywords are kept in the client's [`TypeMap`][typemap], and if a path
+//! is given they're also saved to a file, so that they persist between
+//! restarts. Each line of the file contains a guild ID, a keyword and an emoji,
+//! separated by spaces.
+//!
+//! [typemap]: serenity::prelude::TypeMap
+
+use serenity::{model::id::GuildId, prelude::TypeMapKey};
+use tokio::sync::RwLock;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, ErrorKind},
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// The extra keywords for each guild.
+#[derive(Debug, Default)]
+pub struct Keywords {
+    /// The file the keywords are saved to, if any.
+    path: Option<PathBuf>,
+    /// The emojis for each keyword in each guild.
+    guilds: HashMap<GuildId, BTreeMap<String, Vec<String>>>,
+}
+
+impl TypeMapKey for Keywords {
+    type Value = Arc<RwLock<Keywords>>;
+}
+
+impl Keywords {
+    /// Load the keywords from a file, if a path is given. If the file doesn't
+    /// exist yet, there are no keywords.
+    pub async fn load(path: Option<PathBuf>) -> io::Result<Self> {
+        let mut keywords = Keywords {
+            path,
+            guilds: HashMap::new(),
+        };
+
+        let contents = match &keywords.path {
+            Some(path) => match tokio::fs::read_to_string(path).await {
+                Ok(contents) => contents,
+                Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+                Err(err) => return Err(err),
+            },
+            None => String::new(),
+        };
+
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.split_whitespace();
+
+            match (fields.next().map(str::parse), fields.next(), fields.next()) {
+                (Some(Ok(guild_id)), Some(keyword), Some(emoji)) => keywords
+                    .guilds
+                    .entry(GuildId(guild_id))
+                    .or_default()
+                    .entry(keyword.to_string())
+                    .or_default()
+                    .push(emoji.to_string()),
+                _ => warn!(line, "ignoring invalid line in keywords file"),
+            }
+        }
+
+        Ok(keywords)
+    }
+
+    /// Get the extra keywords for a guild, with one entry for each emoji.
+    pub fn get(&self, guild_id: GuildId) -> Vec<(String, String)> {
+        self.guilds
+            .get(&guild_id)
+            .into_iter()
+            .flatten()
+            .flat_map(|(keyword, emojis)| {
+                emojis
+                    .iter()
+                    .map(move |emoji| (keyword.clone(), emoji.clone()))
+            })
+            .collect()
+    }
+
+    /// Add an emoji for a keyword in a guild, and save the keywords.
+    pub async fn add(
+        &mut self,
+        guild_id: GuildId,
+        keyword: String,
+        emoji: String,
+    ) -> io::Result<()> {
+        let emojis = self
+            .guilds
+            .entry(guild_id)
+            .or_default()
+            .entry(keyword)
+            .or_default();
+
+        if !emojis.contains(&emoji) {
+            emojis.push(emoji);
+        }
+
+        self.save().await
+    }
+
+    /// Remove all of the emojis for a keyword in a guild, and save the
+    /// keywords. Returns whether the keyword existed.
+    pub async fn remove(&mut self, guild_id: GuildId, keyword: &str) -> io::Result<bool> {
+        let removed = self
+            .guilds
+            .get_mut(&guild_id)
+            .and_then(|keywords| keywords.remove(keyword))
+            .is_some();
+
+        if removed {
+            self.save().await?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Save the keywords to the file, if there is one.
+    async fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut contents = String::new();
+
+        for (guild_id, keywords) in &self.guilds {
+            for (keyword, emojis) in keywords {
+                for emoji in emojis {
+                    contents.push_str(&format!("{} {} {}\n", guild_id.0, keyword, emoji));
+                }
+            }
+        }
+
+        tokio::fs::write(path, contents).await
+    }
+}
@@ -10,8 +10,10 @@
 //! ```
 //!
 //! The prefix can be changed from the default (`,`) using `HATYSA_PREFIX`, and
-//! you might also want to [change the tracing subscriber filter][sub] to
-//! customise what log messages are printed out:
+//! the emojipasta keywords added by guild admins are saved to the file at
+//! `HATYSA_KEYWORDS_PATH`, if it's set. You might also want to [change the
+//! tracing subscriber filter][sub] to customise what log messages are printed
+//! out:
 //!
 //! [sub]:
 //! ../tracing_subscriber/fmt/index.html#filtering-events-with-environment-variables
@@ -23,7 +25,9 @@
 #[macro_use]
 extern crate tracing;
 
+pub mod emojis;
 pub mod handler;
+pub mod keywords;
 pub mod task;
 
 use chrono::Utc;
@@ -31,9 +35,11 @@ use eyre::{Result, WrapErr};
 use serenity::prelude::*;
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
-use std::env;
+use std::{env, path::PathBuf, sync::Arc};
 
+use emojis::Emojis;
 use handler::Handler;
+use keywords::Keywords;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -49,6 +55,11 @@ async fn main() -> Result<()> {
     let token = env::var("DISCORD_TOKEN").wrap_err("expected a token in the environment")?;
     let prefix = env::var("HATYSA_PREFIX").unwrap_or_else(|_| ",".to_string());
 
+    let keywords_path = env::var("HATYSA_KEYWORDS_PATH").ok().map(PathBuf::from);
+    let keywords = Keywords::load(keywords_path)
+        .await
+        .wrap_err("failed to load emojipasta keywords")?;
+
     let start_time = Utc::now();
     info!("starting hatysa at {}", start_time);
 
@@ -61,6 +72,12 @@ async fn main() -> Result<()> {
     .event_handler(Handler { prefix, start_time })
     .await?;
 
+    {
+        let mut data = client.data.write().await;
+        data.insert::<Emojis>(Arc::new(RwLock::new(Emojis::default())));
+        data.insert::<Keywords>(Arc::new(RwLock::new(keywords)));
+    }
+
     if let Err(why) = client.start().await {
         error!("Client error: {:?}", why);
     }
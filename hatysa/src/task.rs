@@ -10,31 +10,51 @@ use serenity::{
     client::Context as ClientContext,
     model::{
         channel::{Message, ReactionType},
-        id::MessageId,
+        id::{EmojiId, GuildId, MessageId, RoleId},
+        Permissions,
     },
+    prelude::RwLock,
     utils::MessageBuilder,
 };
 
-use std::time::Duration;
+use std::{io, sync::Arc, time::Duration};
 
-use iota_orionis::command::{Command, CommandError, Response};
+use iota_orionis::command::{Command, CommandError, Reaction, Response, MAX_MESSAGE_LENGTH};
+
+use crate::{emojis::Emojis, handler::split_substitution, keywords::Keywords};
+
+/// The number of messages to search through when looking for the author's
+/// previous message.
+const HISTORY_LIMIT: u64 = 50;
 
 /// A task containing a command and context about the message that triggered the
 /// command.
 pub struct Task {
-    /// The underlying command.
-    command: Command,
+    /// The underlying command, or the error that occurred while parsing it.
+    command: Result<Command, CommandError>,
     /// The context of the command.
     context: Context,
 }
 
 impl Task {
     /// Create a new task from a parsed command, the message that triggered it,
-    /// and the context of the message.
-    pub fn new(command: Command, ctx: ClientContext, message: Message) -> Self {
+    /// the context of the message, and the prefix used for commands.
+    ///
+    /// If the command could not be parsed, the error will be reported to the
+    /// user when the task is executed.
+    pub fn new(
+        command: Result<Command, CommandError>,
+        ctx: ClientContext,
+        message: Message,
+        prefix: String,
+    ) -> Self {
         Self {
             command,
-            context: Context { ctx, message },
+            context: Context {
+                ctx,
+                message,
+                prefix,
+            },
         }
     }
 
@@ -44,8 +64,16 @@ impl Task {
     /// If any step in the process fails, an error will be returned.
     #[instrument(skip(self), fields(id = self.context.message.id.0))]
     pub async fn execute(self) {
-        // First try to execute the command.
-        match self.command.execute().await {
+        // First try to execute the command, if it was parsed successfully.
+        let result = match self.command {
+            Ok(command) => match self.context.resolve(command).await {
+                Ok(command) => command.execute().await,
+                Err(err) => Err(err),
+            },
+            Err(err) => Err(err),
+        };
+
+        match result {
             Ok(response) => {
                 // If execute() succeeded, then the command was valid and we
                 // have some info to send back to the user.
@@ -82,6 +110,8 @@ struct Context {
     ctx: ClientContext,
     /// The message that triggered the command.
     message: Message,
+    /// The prefix used for commands.
+    prefix: String,
 }
 
 impl Context {
@@ -89,14 +119,205 @@ impl Context {
     #[instrument(skip(self))]
     async fn respond(&self, response: Response) -> Result<(), TaskError> {
         match response {
-            Response::Clap { output }
+            Response::Atbash { output }
+            | Response::Banner { output }
+            | Response::Braille { output }
+            | Response::Caesar { output }
+            | Response::Cowsay { output }
+            | Response::Decode { output }
+            | Response::Dialect { output }
+            | Response::Drunk { output }
+            | Response::Emojify { output }
+            | Response::Emojipasta { output }
+            | Response::Encode { output }
+            | Response::Fancy { output }
+            | Response::Flip { output }
+            | Response::Leet { output }
+            | Response::Mirror { output }
+            | Response::Morse { output }
+            | Response::Normalize { output }
+            | Response::Overlay { output }
+            | Response::Rot13 { output }
+            | Response::Scramble { output }
+            | Response::SmallCaps { output }
+            | Response::Spoiler { output }
             | Response::Spongebob { output }
+            | Response::Subscript { output }
+            | Response::Substitute { output }
+            | Response::Superscript { output }
+            | Response::Unleet { output }
+            | Response::Unmorse { output }
+            | Response::Uwu { output }
+            | Response::Vigenere { output }
             | Response::Wavy { output }
             | Response::Zalgo { output } => {
                 debug!("sending output in a plain message");
 
                 self.message.channel_id.say(&self.ctx.http, output).await?;
             }
+            Response::Clap { output, emoji } => {
+                // Custom emojis only show up in messages if the bot can use them.
+                if let Reaction::Custom { id, name, .. } = &emoji {
+                    if !self.can_use_emoji(EmojiId(*id)).await {
+                        return self
+                            .report(CommandError::UnavailableEmoji { name: name.clone() })
+                            .await;
+                    }
+                }
+
+                self.message.channel_id.say(&self.ctx.http, output).await?;
+            }
+            Response::CaesarBruteforce { shifts } => {
+                self.message
+                    .channel_id
+                    .send_message(&self.ctx.http, |m| {
+                        debug!("constructing embed");
+
+                        let mut embed = CreateEmbed::default();
+
+                        embed.title("Caesar shifts").colour((244, 234, 62));
+
+                        for (shift, output) in shifts {
+                            embed.field(format!("Shift {}", shift), output, true);
+                        }
+
+                        m.set_embed(embed)
+                    })
+                    .await?;
+            }
+            Response::CharInfo { pages } => {
+                let count = pages.len();
+
+                for (i, page) in pages.iter().enumerate() {
+                    let mut description = MessageBuilder::new();
+
+                    for info in page {
+                        description.push_mono(info.code()).push(" ");
+
+                        if let Some(display) = info.display() {
+                            description.push_bold_safe(display).push(" ");
+                        }
+
+                        description
+                            .push(&info.name)
+                            .push(" (")
+                            .push(info.category_name)
+                            .push(")\n");
+                    }
+
+                    self.message
+                        .channel_id
+                        .send_message(&self.ctx.http, |m| {
+                            debug!(page = i + 1, "constructing embed");
+
+                            let mut embed = CreateEmbed::default();
+
+                            embed
+                                .title("Character info")
+                                .description(description.build())
+                                .colour((244, 234, 62));
+
+                            if count > 1 {
+                                embed.footer(|f| f.text(format!("Page {} of {}", i + 1, count)));
+                            }
+
+                            m.set_embed(embed)
+                        })
+                        .await?;
+                }
+            }
+            Response::EmojipastaAdd { keyword, emoji } => {
+                let guild_id = match self.managed_guild().await {
+                    Some(guild_id) => guild_id,
+                    None => return self.report(CommandError::NotPermitted).await,
+                };
+
+                // Custom emojis only show up in messages if the bot can use them.
+                if let Reaction::Custom { id, name, .. } = &emoji {
+                    if !self.can_use_emoji(EmojiId(*id)).await {
+                        return self
+                            .report(CommandError::UnavailableEmoji { name: name.clone() })
+                            .await;
+                    }
+                }
+
+                let emoji = emoji.to_string();
+
+                self.keywords()
+                    .await?
+                    .write()
+                    .await
+                    .add(guild_id, keyword.clone(), emoji.clone())
+                    .await?;
+
+                let content = MessageBuilder::new()
+                    .push("Added ")
+                    .push(emoji)
+                    .push(" for ")
+                    .push_bold_safe(keyword)
+                    .push("!")
+                    .build();
+
+                self.message.channel_id.say(&self.ctx.http, content).await?;
+            }
+            Response::EmojipastaKeywords { keywords } => {
+                let content = if keywords.is_empty() {
+                    "No keywords have been added in this server.".to_string()
+                } else {
+                    let mut content = MessageBuilder::new();
+
+                    for (keyword, emoji) in keywords {
+                        content.push_bold_safe(keyword).push(" ").push_line(emoji);
+                    }
+
+                    content.build()
+                };
+
+                let length = content.chars().count();
+
+                if length > MAX_MESSAGE_LENGTH {
+                    warn!("keyword listing is too long");
+
+                    return self
+                        .report(CommandError::TooLong {
+                            length,
+                            max_chars: MAX_MESSAGE_LENGTH,
+                        })
+                        .await;
+                }
+
+                self.message.channel_id.say(&self.ctx.http, content).await?;
+            }
+            Response::EmojipastaRemove { keyword } => {
+                let guild_id = match self.managed_guild().await {
+                    Some(guild_id) => guild_id,
+                    None => return self.report(CommandError::NotPermitted).await,
+                };
+
+                let removed = self
+                    .keywords()
+                    .await?
+                    .write()
+                    .await
+                    .remove(guild_id, &keyword)
+                    .await?;
+
+                let content = if removed {
+                    MessageBuilder::new()
+                        .push("Removed ")
+                        .push_bold_safe(keyword)
+                        .push("!")
+                        .build()
+                } else {
+                    MessageBuilder::new()
+                        .push("Keyword ")
+                        .push_bold_safe(keyword)
+                        .push(" hasn't been added!")
+                        .build()
+                };
+
+                self.message.channel_id.say(&self.ctx.http, content).await?;
+            }
             Response::Info {
                 version,
                 uptime: (days, hours, minutes, seconds),
@@ -142,6 +363,20 @@ impl Context {
                 self.message.channel_id.say(&self.ctx.http, "Pong!").await?;
             }
             Response::React { reactions } => {
+                debug!("checking custom emojis are available");
+
+                // Custom emojis can only be used if they belong to a guild the
+                // bot is in, so check them all before reacting with any.
+                for reaction in &reactions {
+                    if let Reaction::Custom { id, name, .. } = reaction {
+                        if !self.can_use_emoji(EmojiId(*id)).await {
+                            return self
+                                .report(CommandError::UnavailableEmoji { name: name.clone() })
+                                .await;
+                        }
+                    }
+                }
+
                 debug!("determining reaction target");
 
                 // Find the message to react to.
@@ -162,7 +397,14 @@ impl Context {
                 debug!("adding reactions");
 
                 // React to the message.
-                for reaction in reactions.into_iter().map(ReactionType::Unicode) {
+                for reaction in reactions.into_iter().map(|reaction| match reaction {
+                    Reaction::Unicode(emoji) => ReactionType::Unicode(emoji),
+                    Reaction::Custom { animated, id, name } => ReactionType::Custom {
+                        animated,
+                        id: EmojiId(id),
+                        name: Some(name),
+                    },
+                }) {
                     target
                         .react(&self.ctx.http, reaction)
                         .await
@@ -244,6 +486,126 @@ impl Context {
         Ok(target.id)
     }
 
+    /// Fill in the parts of a command that depend on the messages around it,
+    /// which for a [`Command::Substitute`] is the message to correct.
+    #[instrument(skip(self, command))]
+    async fn resolve(&self, command: Command) -> Result<Command, CommandError> {
+        match command {
+            Command::Substitute {
+                pattern,
+                replacement,
+                global,
+                ignore_case,
+                ..
+            } => Ok(Command::Substitute {
+                input: self
+                    .find_previous_content()
+                    .await
+                    .ok_or(CommandError::NoPreviousMessage)?,
+                pattern,
+                replacement,
+                global,
+                ignore_case,
+            }),
+            command => Ok(command),
+        }
+    }
+
+    /// Find the content of the most recent message the author sent in the same
+    /// channel before `self.message`, skipping any earlier substitutions.
+    #[instrument(skip(self))]
+    async fn find_previous_content(&self) -> Option<String> {
+        debug!("searching for the author's previous messages");
+
+        let messages = self
+            .message
+            .channel_id
+            .messages(&self.ctx.http, |retriever| {
+                retriever.before(self.message.id).limit(HISTORY_LIMIT)
+            })
+            .await;
+
+        match messages {
+            Ok(messages) => messages
+                .into_iter()
+                .find(|prev| {
+                    prev.author.id == self.message.author.id
+                        && !prev.content.is_empty()
+                        && split_substitution(&prev.content, &self.prefix).is_none()
+                })
+                .map(|prev| prev.content),
+            Err(err) => {
+                warn!("unable to get previous messages: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Determine whether the bot is able to use the custom emoji with the given
+    /// ID, which is only the case if it belongs to one of the bot's guilds.
+    #[instrument(skip(self))]
+    async fn can_use_emoji(&self, emoji_id: EmojiId) -> bool {
+        let data = self.ctx.data.read().await;
+
+        let found = match data.get::<Emojis>() {
+            Some(emojis) => emojis.read().await.contains(emoji_id),
+            None => false,
+        };
+
+        if found {
+            debug!("found emoji in one of the bot's guilds");
+        } else {
+            warn!("emoji does not belong to any available guild");
+        }
+
+        found
+    }
+
+    /// Get the extra emojipasta keywords.
+    async fn keywords(&self) -> Result<Arc<RwLock<Keywords>>, TaskError> {
+        self.ctx
+            .data
+            .read()
+            .await
+            .get::<Keywords>()
+            .cloned()
+            .ok_or(TaskError::MissingKeywords)
+    }
+
+    /// If the message was sent in a guild by a member who can manage the guild,
+    /// return the ID of the guild.
+    async fn managed_guild(&self) -> Option<GuildId> {
+        let guild_id = self.message.guild_id?;
+        let guild = guild_id.to_partial_guild(&self.ctx.http).await.ok()?;
+
+        if guild.owner_id == self.message.author.id {
+            return Some(guild_id);
+        }
+
+        let member = guild_id
+            .member(&self.ctx.http, self.message.author.id)
+            .await
+            .ok()?;
+
+        // Everyone has the permissions of the @everyone role, which has the
+        // same ID as the guild.
+        let permissions = member
+            .roles
+            .iter()
+            .chain(Some(&RoleId(guild_id.0)))
+            .filter_map(|role_id| guild.roles.get(role_id))
+            .fold(Permissions::empty(), |permissions, role| {
+                permissions | role.permissions
+            });
+
+        if permissions.intersects(Permissions::ADMINISTRATOR | Permissions::MANAGE_GUILD) {
+            Some(guild_id)
+        } else {
+            debug!("user does not have permission to manage guild");
+            None
+        }
+    }
+
     /// Attempt to report a command error to the user.
     #[instrument(skip(self), fields(channel_id = self.message.channel_id.0))]
     async fn report(&self, err: CommandError) -> Result<(), TaskError> {
@@ -281,6 +643,64 @@ impl Context {
                                 .push_bold(original.to_uppercase())
                                 .push(" contains repeated characters!")
                                 .build(),
+                            CommandError::UnavailableEmoji { ref name } => MessageBuilder::new()
+                                .push("Emoji ")
+                                .push_bold_safe(name)
+                                .push(" isn't available to me!")
+                                .build(),
+                            CommandError::EmptyInput => MessageBuilder::new()
+                                .push("There's nothing to work with!")
+                                .build(),
+                            CommandError::InvalidArgument {
+                                ref argument,
+                                ref value,
+                            } => MessageBuilder::new()
+                                .push("Invalid value ")
+                                .push_bold_safe(value)
+                                .push(" for ")
+                                .push_mono_safe(argument)
+                                .push("!")
+                                .build(),
+                            CommandError::TooLong { max_chars, .. } => MessageBuilder::new()
+                                .push("Output would be longer than the limit of ")
+                                .push_bold(max_chars)
+                                .push(" characters!")
+                                .build(),
+                            CommandError::InvalidEncoding { encoding } => MessageBuilder::new()
+                                .push("Input isn't valid ")
+                                .push_bold(encoding)
+                                .push("!")
+                                .build(),
+                            CommandError::InvalidMorse { ref symbols } => {
+                                let mut message = MessageBuilder::new();
+                                message.push("Couldn't decode ");
+
+                                for (i, symbol) in symbols.iter().enumerate() {
+                                    if i > 0 {
+                                        message.push(", ");
+                                    }
+
+                                    message.push_mono_safe(symbol);
+                                }
+
+                                message.push(" as Morse code!").build()
+                            }
+                            CommandError::InvalidRegex { ref pattern } => MessageBuilder::new()
+                                .push("Couldn't compile ")
+                                .push_mono_safe(pattern)
+                                .push(" as a regular expression!")
+                                .build(),
+                            CommandError::NoMatch { ref pattern } => MessageBuilder::new()
+                                .push("Pattern ")
+                                .push_mono_safe(pattern)
+                                .push(" doesn't match your previous message!")
+                                .build(),
+                            CommandError::NoPreviousMessage => MessageBuilder::new()
+                                .push("Couldn't find a previous message of yours to correct!")
+                                .build(),
+                            CommandError::NotPermitted => MessageBuilder::new()
+                                .push("You need the Manage Server permission to do that!")
+                                .build(),
                             CommandError::InvalidUrl(_) => {
                                 MessageBuilder::new().push("Invalid URL!").build()
                             }
@@ -376,6 +796,10 @@ pub enum TaskError {
     GetPrevious { message_id: MessageId },
     #[error("unable to delete message {}", message_id)]
     Delete { message_id: MessageId },
+    #[error("unable to access emojipasta keywords")]
+    MissingKeywords,
+    #[error("unable to save emojipasta keywords: {0}")]
+    SaveKeywords(#[from] io::Error),
     #[error("unable to report command error: {0}")]
     ReportError(#[from] CommandError),
 }
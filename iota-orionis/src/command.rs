@@ -1,23 +1,223 @@
 //! Execute commands and return their output.
 
+mod banner;
+mod braille;
+mod charinfo;
+mod cipher;
 mod clap;
+mod cowsay;
+mod dialect;
+mod drunk;
+mod emojify;
+mod emojipasta;
+mod encoding;
+mod fancy;
+mod flip;
 mod info;
+mod leet;
+mod markup;
+mod morse;
+mod normalize;
+mod overlay;
 mod react;
+mod scramble;
 mod sketchify;
+mod small;
+mod spoiler;
 mod spongebob;
+mod substitute;
+mod uwu;
 mod wavy;
 mod zalgo;
 
 use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, SeedableRng};
 use url::{ParseError, Url};
 
+use std::{fmt, str::FromStr};
+
+pub use banner::Font;
+pub use charinfo::CharInfo;
+pub use cipher::Operation;
+pub use clap::DEFAULT_SEPARATOR;
+pub use cowsay::{DEFAULT_COW, DEFAULT_WIDTH};
+pub use dialect::Dialect;
+pub use encoding::Encoding;
+pub use fancy::Style;
+pub use flip::{flip_text, mirror_text};
+pub use leet::unleet_text;
+pub use normalize::normalize_text;
+pub use overlay::Overlay;
+pub use spoiler::Granularity;
+pub use zalgo::Direction;
+
+/// The maximum number of characters that can be sent in a single Discord
+/// message.
+pub const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Create the random number generator for a command, seeded with the given
+/// seed if there is one so that the output can be reproduced.
+fn rng(seed: Option<u64>) -> StdRng {
+    seed.map(StdRng::seed_from_u64)
+        .unwrap_or_else(StdRng::from_entropy)
+}
+
+/// Check that the output of a command fits in a single message, returning it
+/// if it does.
+fn check_length(output: String) -> Result<String, CommandError> {
+    let length = output.chars().count();
+
+    if length > MAX_MESSAGE_LENGTH {
+        warn!("output is too long");
+
+        Err(CommandError::TooLong {
+            length,
+            max_chars: MAX_MESSAGE_LENGTH,
+        })
+    } else {
+        Ok(output)
+    }
+}
+
 /// Commands that can be performed.
 #[derive(Debug)]
 pub enum Command {
+    /// Encrypt or decrypt text with the Atbash cipher, which reverses the
+    /// alphabet.
+    Atbash {
+        /// The input to convert.
+        input: String,
+    },
+    /// Render text as large ASCII art letters.
+    Banner {
+        /// The input to render.
+        input: String,
+        /// The font to render the input in.
+        font: Font,
+    },
+    /// Convert text to braille.
+    Braille {
+        /// The input to convert.
+        input: String,
+    },
+    /// Encrypt text with a Caesar cipher.
+    Caesar {
+        /// The input to convert.
+        input: String,
+        /// How many places to shift each letter forward. Negative shifts move
+        /// letters backwards.
+        shift: i64,
+    },
+    /// Show the input shifted by every possible Caesar shift, to find the one
+    /// that decrypts it.
+    CaesarBruteforce {
+        /// The input to convert.
+        input: String,
+    },
+    /// Describe each codepoint in the input.
+    CharInfo {
+        /// The input to describe.
+        input: String,
+    },
     /// Insert clapping emojis between every word of the input text.
     Clap {
         /// The input to convert.
         input: String,
+        /// The separator to insert between words, which is [`DEFAULT_SEPARATOR`]
+        /// (a clapping emoji) unless another emoji is wanted instead. It must
+        /// be a single Unicode or custom emoji.
+        separator: String,
+        /// Whether to add a separator after the last word of each line as well.
+        trailing: bool,
+    },
+    /// Have a cow say or think the input, in a speech or thought bubble.
+    Cowsay {
+        /// The input to say.
+        input: String,
+        /// The name of the cow file to draw.
+        cow: String,
+        /// The width to wrap the input to.
+        width: usize,
+        /// Whether to think the input, with a thought bubble, rather than say
+        /// it.
+        think: bool,
+    },
+    /// Decode text from an encoding.
+    Decode {
+        /// The encoded input.
+        input: String,
+        /// The encoding to decode from.
+        encoding: Encoding,
+    },
+    /// Translate text into a dialect, such as pirate speak.
+    Dialect {
+        /// The input to translate.
+        input: String,
+        /// The dialect to translate into.
+        dialect: Dialect,
+    },
+    /// Make text look like it was typed while drunk.
+    Drunk {
+        /// The input to convert.
+        input: String,
+        /// How many typos to add.
+        intensity: Intensity,
+        /// If provided, the seed to use, so that the same input and seed always
+        /// produce the same output.
+        seed: Option<u64>,
+    },
+    /// Write out the letters and numbers of the input as emojis.
+    Emojify {
+        /// The input to convert.
+        input: String,
+    },
+    /// Add relevant emojis after words in the input.
+    Emojipasta {
+        /// The input to convert.
+        input: String,
+        /// If provided, the seed to use, so that the same input and seed always
+        /// produce the same output.
+        seed: Option<u64>,
+        /// Extra keywords to use alongside the bundled ones, each with an emoji
+        /// to add after it.
+        extra: Vec<(String, String)>,
+    },
+    /// Add a keyword to the keywords used by [Command::Emojipasta].
+    EmojipastaAdd {
+        /// The keyword to add.
+        keyword: String,
+        /// The emoji to add after the keyword.
+        emoji: String,
+    },
+    /// List the extra keywords used by [Command::Emojipasta].
+    EmojipastaKeywords {
+        /// The extra keywords, each with the emoji to add after it.
+        keywords: Vec<(String, String)>,
+    },
+    /// Remove a keyword added with [Command::EmojipastaAdd].
+    EmojipastaRemove {
+        /// The keyword to remove.
+        keyword: String,
+    },
+    /// Encode text in an encoding.
+    Encode {
+        /// The input to encode.
+        input: String,
+        /// The encoding to use.
+        encoding: Encoding,
+    },
+    /// Convert text to a fancy style, such as bold, script or double-struck,
+    /// using Unicode mathematical alphanumeric symbols.
+    Fancy {
+        /// The input to convert.
+        input: String,
+        /// The style to convert to.
+        style: Style,
+    },
+    /// Turn text upside down.
+    Flip {
+        /// The input to convert.
+        input: String,
     },
     /// A request from a user for some information about the currently running
     /// instance of the bot.
@@ -25,14 +225,60 @@ pub enum Command {
         /// The start time of this bot instance.
         start_time: DateTime<Utc>,
     },
+    /// Convert text to leetspeak.
+    Leet {
+        /// The input to convert.
+        input: String,
+        /// How obscure the output should be, from 1 (simple digit
+        /// substitutions) to 3 (multi-character glyphs for every letter).
+        level: u8,
+    },
+    /// Mirror text horizontally.
+    Mirror {
+        /// The input to convert.
+        input: String,
+    },
+    /// Encode text as Morse code.
+    Morse {
+        /// The input to encode.
+        input: String,
+    },
+    /// Convert decorated text, such as Zalgo or vaporwave text, back into plain
+    /// text.
+    Normalize {
+        /// The input to convert.
+        input: String,
+    },
+    /// Draw a line over, under or through every character of the input, using
+    /// combining characters.
+    Overlay {
+        /// The input to convert.
+        input: String,
+        /// The line to draw.
+        overlay: Overlay,
+    },
     /// A request from a user for a response, to check if the bot is alive.
     Ping,
     /// Convert an input string into a series of emojis that can then be used to
-    /// react to a message.
+    /// react to a message. Emojis already present in the input, including
+    /// custom emojis, are kept in their original positions.
     React {
         /// The string to convert to emojis.
         input: String,
     },
+    /// Encrypt or decrypt text with ROT13.
+    Rot13 {
+        /// The input to convert.
+        input: String,
+    },
+    /// Shuffle the letters in the middle of each word of the input.
+    Scramble {
+        /// The input to convert.
+        input: String,
+        /// If provided, the seed to use, so that the same input and seed always
+        /// produce the same output.
+        seed: Option<u64>,
+    },
     /// Convert a URL to a "sketchified" equivalent using [the Sketchify
     /// API][sketchify].
     ///
@@ -41,10 +287,82 @@ pub enum Command {
         /// The string provided for the URL to sketchify.
         url_raw: String,
     },
+    /// Convert text to small capital letters.
+    SmallCaps {
+        /// The input to convert.
+        input: String,
+    },
+    /// Hide text behind spoiler tags, one word or character at a time.
+    Spoiler {
+        /// The input to hide.
+        input: String,
+        /// Whether to hide each word or each character separately.
+        granularity: Granularity,
+    },
     /// Convert text to Spongebob-case text.
     Spongebob {
         /// The input to convert.
         input: String,
+        /// Whether to choose the case of each character randomly, rather than
+        /// strictly alternating.
+        random: bool,
+        /// If provided, the seed to use when choosing cases randomly.
+        seed: Option<u64>,
+    },
+    /// Convert text to subscript characters.
+    Subscript {
+        /// The input to convert.
+        input: String,
+    },
+    /// Apply a sed-style substitution to the input, such as a previous message
+    /// that needs correcting.
+    Substitute {
+        /// The input to correct.
+        input: String,
+        /// The regular expression to search for.
+        pattern: String,
+        /// What to replace matches with, where `&` is the whole match and `\1`
+        /// to `\9` are capture groups.
+        replacement: String,
+        /// Whether to replace every match, rather than just the first.
+        global: bool,
+        /// Whether to ignore case when matching the pattern.
+        ignore_case: bool,
+    },
+    /// Convert text to superscript characters.
+    Superscript {
+        /// The input to convert.
+        input: String,
+    },
+    /// Decode leetspeak back into plain text.
+    Unleet {
+        /// The input to convert.
+        input: String,
+    },
+    /// Decode Morse code back into text.
+    Unmorse {
+        /// The Morse code to decode, with letters separated by spaces and words
+        /// separated by `/`.
+        input: String,
+    },
+    /// Convert text to uwu-speak.
+    Uwu {
+        /// The input to convert.
+        input: String,
+        /// How often to add stutters and faces.
+        intensity: Intensity,
+        /// If provided, the seed to use, so that the same input and seed always
+        /// produce the same output.
+        seed: Option<u64>,
+    },
+    /// Encrypt or decrypt text with a Vigenère cipher.
+    Vigenere {
+        /// The input to convert.
+        input: String,
+        /// The key, which can only contain letters.
+        key: String,
+        /// Whether to encrypt or decrypt the input.
+        operation: Operation,
     },
     /// Convert text to vaporwave (fullwidth) text.
     Wavy {
@@ -55,8 +373,18 @@ pub enum Command {
     Zalgo {
         /// The input to convert.
         input: String,
-        /// If provided, the maximum number of characters to output.
+        /// If provided, the maximum number of characters to output. The output
+        /// never exceeds this, and an input that is already longer results in
+        /// an error.
         max_chars: Option<usize>,
+        /// How heavily to decorate each character.
+        intensity: Intensity,
+        /// Where to place combining characters. If empty, all directions are
+        /// used.
+        directions: Vec<Direction>,
+        /// If provided, the seed to use, so that the same input and seed always
+        /// produce the same output.
+        seed: Option<u64>,
     },
 }
 
@@ -64,14 +392,87 @@ impl Command {
     /// Execute a command, returning its response.
     pub async fn execute(self) -> Result<Response, CommandError> {
         match self {
-            Command::Clap { input } => Ok(clap::clap(input)),
+            Command::Atbash { input } => Ok(cipher::atbash(input)),
+            Command::Banner { input, font } => banner::banner(input, font),
+            Command::Braille { input } => braille::braille(input),
+            Command::Caesar { input, shift } => Ok(cipher::caesar(input, shift)),
+            Command::CaesarBruteforce { input } => cipher::caesar_bruteforce(input),
+            Command::CharInfo { input } => charinfo::charinfo(input),
+            Command::Clap {
+                input,
+                separator,
+                trailing,
+            } => clap::clap(input, separator, trailing),
+            Command::Cowsay {
+                input,
+                cow,
+                width,
+                think,
+            } => cowsay::cowsay(input, cow, width, think),
+            Command::Decode { input, encoding } => encoding::decode(input, encoding),
+            Command::Dialect { input, dialect } => dialect::dialect(input, dialect),
+            Command::Drunk {
+                input,
+                intensity,
+                seed,
+            } => Ok(drunk::drunk(input, intensity, seed)),
+            Command::Emojify { input } => emojify::emojify(input),
+            Command::Emojipasta { input, seed, extra } => {
+                emojipasta::emojipasta(input, seed, extra)
+            }
+            Command::EmojipastaAdd { keyword, emoji } => emojipasta::add_keyword(keyword, emoji),
+            Command::EmojipastaKeywords { keywords } => Ok(emojipasta::list_keywords(keywords)),
+            Command::EmojipastaRemove { keyword } => emojipasta::remove_keyword(keyword),
+            Command::Encode { input, encoding } => encoding::encode(input, encoding),
+            Command::Fancy { input, style } => Ok(fancy::fancy(input, style)),
+            Command::Flip { input } => Ok(flip::flip(input)),
             Command::Info { start_time } => Ok(info::info(start_time).await),
+            Command::Leet { input, level } => leet::leet(input, level),
+            Command::Mirror { input } => Ok(flip::mirror(input)),
+            Command::Morse { input } => morse::morse(input),
+            Command::Normalize { input } => Ok(normalize::normalize(input)),
+            Command::Overlay { input, overlay } => overlay::overlay(input, overlay),
             Command::Ping => Ok(Response::Pong),
             Command::React { input } => react::react(input),
+            Command::Rot13 { input } => Ok(cipher::rot13(input)),
+            Command::Scramble { input, seed } => Ok(scramble::scramble(input, seed)),
             Command::Sketchify { url_raw } => sketchify::sketchify(url_raw).await,
-            Command::Spongebob { input } => Ok(spongebob::spongebob(input)),
+            Command::SmallCaps { input } => Ok(small::smallcaps(input)),
+            Command::Spoiler { input, granularity } => spoiler::spoiler(input, granularity),
+            Command::Spongebob {
+                input,
+                random,
+                seed,
+            } => Ok(spongebob::spongebob(input, random, seed)),
+            Command::Subscript { input } => Ok(small::subscript(input)),
+            Command::Substitute {
+                input,
+                pattern,
+                replacement,
+                global,
+                ignore_case,
+            } => substitute::substitute(input, pattern, replacement, global, ignore_case),
+            Command::Superscript { input } => Ok(small::superscript(input)),
+            Command::Unleet { input } => Ok(leet::unleet(input)),
+            Command::Unmorse { input } => morse::unmorse(input),
+            Command::Uwu {
+                input,
+                intensity,
+                seed,
+            } => Ok(uwu::uwu(input, intensity, seed)),
+            Command::Vigenere {
+                input,
+                key,
+                operation,
+            } => cipher::vigenere(input, key, operation),
             Command::Wavy { input } => wavy::wavy(input),
-            Command::Zalgo { input, max_chars } => Ok(zalgo::zalgo(input, max_chars)),
+            Command::Zalgo {
+                input,
+                max_chars,
+                intensity,
+                directions,
+                seed,
+            } => zalgo::zalgo(input, max_chars, intensity, directions, seed),
         }
     }
 }
@@ -79,10 +480,105 @@ impl Command {
 /// Possible responses as a result of a command.
 #[derive(Debug)]
 pub enum Response {
+    /// Response to a [Command::Atbash].
+    Atbash {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Banner].
+    Banner {
+        /// The rendered input, in a code block.
+        output: String,
+    },
+    /// Response to a [Command::Braille].
+    Braille {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Caesar].
+    Caesar {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::CaesarBruteforce].
+    CaesarBruteforce {
+        /// The input shifted by each of the shifts from 1 to 25.
+        shifts: Vec<(u8, String)>,
+    },
+    /// Response to a [Command::CharInfo].
+    CharInfo {
+        /// Information about each codepoint in the input, split into pages.
+        pages: Vec<Vec<CharInfo>>,
+    },
     /// Response to a [Command::Clap].
     Clap {
         /// The converted input.
         output: String,
+        /// The emoji used as the separator, which may be a custom emoji that
+        /// has to be checked before it's used.
+        emoji: Reaction,
+    },
+    /// Response to a [Command::Cowsay].
+    Cowsay {
+        /// The cow and its bubble, in a code block.
+        output: String,
+    },
+    /// Response to a [Command::Decode].
+    Decode {
+        /// The decoded input, or a hex dump of it if it isn't valid UTF-8.
+        output: String,
+    },
+    /// Response to a [Command::Dialect].
+    Dialect {
+        /// The translated input.
+        output: String,
+    },
+    /// Response to a [Command::Drunk].
+    Drunk {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Emojify].
+    Emojify {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Emojipasta].
+    Emojipasta {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::EmojipastaAdd].
+    EmojipastaAdd {
+        /// The keyword to add, in lowercase.
+        keyword: String,
+        /// The emoji to add after the keyword.
+        emoji: Reaction,
+    },
+    /// Response to a [Command::EmojipastaKeywords].
+    EmojipastaKeywords {
+        /// The extra keywords, sorted, each with the emoji to add after it.
+        keywords: Vec<(String, String)>,
+    },
+    /// Response to a [Command::EmojipastaRemove].
+    EmojipastaRemove {
+        /// The keyword to remove, in lowercase.
+        keyword: String,
+    },
+    /// Response to a [Command::Encode].
+    Encode {
+        /// The encoded input.
+        output: String,
+    },
+    /// Response to a [Command::Fancy].
+    Fancy {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Flip].
+    Flip {
+        /// The converted input.
+        output: String,
     },
     /// Response to a [Command::Info].
     Info {
@@ -93,23 +589,103 @@ pub enum Response {
         /// The homepage of the bot.
         homepage: String,
     },
+    /// Response to a [Command::Leet].
+    Leet {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Mirror].
+    Mirror {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Morse].
+    Morse {
+        /// The encoded input.
+        output: String,
+    },
+    /// Response to a [Command::Normalize].
+    Normalize {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Overlay].
+    Overlay {
+        /// The converted input.
+        output: String,
+    },
     /// Response to a [Command::Ping].
     Pong,
     /// Response to a [Command::React].
     React {
         /// A sequence of emojis created to represent the input string.
-        reactions: Vec<String>,
+        reactions: Vec<Reaction>,
+    },
+    /// Response to a [Command::Rot13].
+    Rot13 {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Scramble].
+    Scramble {
+        /// The converted input.
+        output: String,
     },
     /// Response to a [Command::Sketchify].
     Sketchify {
         /// The converted URL.
         url: Url,
     },
+    /// Response to a [Command::SmallCaps].
+    SmallCaps {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Spoiler].
+    Spoiler {
+        /// The input with each piece in spoiler tags.
+        output: String,
+    },
     /// Response to a [Command::Spongebob].
     Spongebob {
         /// The converted input.
         output: String,
     },
+    /// Response to a [Command::Subscript].
+    Subscript {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Substitute].
+    Substitute {
+        /// The corrected input.
+        output: String,
+    },
+    /// Response to a [Command::Superscript].
+    Superscript {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Unleet].
+    Unleet {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Unmorse].
+    Unmorse {
+        /// The decoded input.
+        output: String,
+    },
+    /// Response to a [Command::Uwu].
+    Uwu {
+        /// The converted input.
+        output: String,
+    },
+    /// Response to a [Command::Vigenere].
+    Vigenere {
+        /// The converted input.
+        output: String,
+    },
     /// Response to a [Command::Wavy].
     Wavy {
         /// The converted input.
@@ -122,6 +698,63 @@ pub enum Response {
     },
 }
 
+/// How heavily to transform the input, in commands that can be toned up or
+/// down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Intensity {
+    /// Only a light transformation.
+    Mini,
+    /// A moderate transformation.
+    #[default]
+    Normal,
+    /// As heavy a transformation as possible.
+    Max,
+}
+
+impl FromStr for Intensity {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "mini" => Ok(Intensity::Mini),
+            "normal" => Ok(Intensity::Normal),
+            "max" => Ok(Intensity::Max),
+            _ => Err(CommandError::InvalidArgument {
+                argument: "intensity".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// An emoji that can be used to react to a message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Reaction {
+    /// A standard Unicode emoji, such as a regional indicator.
+    Unicode(String),
+    /// A custom emoji belonging to a guild.
+    Custom {
+        /// Whether the emoji is animated.
+        animated: bool,
+        /// The ID of the emoji.
+        id: u64,
+        /// The name of the emoji.
+        name: String,
+    },
+}
+
+impl fmt::Display for Reaction {
+    /// Format the emoji as it would be written in a message.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reaction::Unicode(emoji) => write!(f, "{}", emoji),
+            Reaction::Custom { animated, id, name } => {
+                write!(f, "<{}:{}:{}>", if *animated { "a" } else { "" }, name, id)
+            }
+        }
+    }
+}
+
 /// Errors that could occur during command processing.
 #[derive(thiserror::Error, Debug)]
 pub enum CommandError {
@@ -129,6 +762,30 @@ pub enum CommandError {
     NonAlphanumeric { original: String },
     #[error("string \"{}\" contains repeated characters", original)]
     Repetition { original: String },
+    #[error("custom emoji \"{}\" is not available", name)]
+    UnavailableEmoji { name: String },
+    #[error("input is empty")]
+    EmptyInput,
+    #[error("invalid value \"{}\" for argument \"{}\"", value, argument)]
+    InvalidArgument { argument: String, value: String },
+    #[error(
+        "output of {} characters would exceed the limit of {}",
+        length,
+        max_chars
+    )]
+    TooLong { length: usize, max_chars: usize },
+    #[error("input is not valid {}", encoding)]
+    InvalidEncoding { encoding: Encoding },
+    #[error("invalid Morse code symbols: {}", symbols.join(", "))]
+    InvalidMorse { symbols: Vec<String> },
+    #[error("pattern \"{}\" is not a valid regular expression", pattern)]
+    InvalidRegex { pattern: String },
+    #[error("pattern \"{}\" does not match", pattern)]
+    NoMatch { pattern: String },
+    #[error("no previous message to correct")]
+    NoPreviousMessage,
+    #[error("user does not have permission to use this command")]
+    NotPermitted,
     #[error("invalid URL: {0}")]
     InvalidUrl(#[from] ParseError),
     #[error("could not complete request: {0}")]
@@ -136,3 +793,13 @@ pub enum CommandError {
     #[error("internal error: {0}")]
     Internal(String),
 }
+
+/// Determine whether a grapheme is an emoji sequence made from otherwise
+/// ordinary characters, such as the keycap sequence 1️⃣, by checking for U+FE0F
+/// VARIATION SELECTOR-16 or U+20E3 COMBINING ENCLOSING KEYCAP.
+///
+/// Transforms that map ASCII characters to other characters should leave these
+/// sequences alone, since changing their characters would break the emoji.
+fn is_emoji_sequence(grapheme: &str) -> bool {
+    grapheme.contains(['\u{fe0f}', '\u{20e3}'])
+}
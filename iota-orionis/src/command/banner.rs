@@ -0,0 +1,257 @@
+//! Render text as large ASCII art letters, using bundled FIGlet fonts.
+//!
+//! Only the parts of the FIGlet font format that the bundled fonts use are
+//! supported: the required ASCII characters, hardblanks and kerning.
+
+use std::{collections::HashMap, str::FromStr};
+
+use super::{check_length, normalize::fold_text, CommandError, Response};
+
+/// The widest a line of the banner can be before it's wrapped, so that it fits
+/// in a code block on most screens without wrapping.
+const MAX_WIDTH: usize = 60;
+
+/// A font to render banners in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Font {
+    /// Upright block letters.
+    #[default]
+    Block,
+    /// Block letters slanted to the right.
+    Slant,
+}
+
+impl Font {
+    /// The contents of the font file.
+    fn data(self) -> &'static str {
+        match self {
+            Font::Block => include_str!("banner/block.flf"),
+            Font::Slant => include_str!("banner/slant.flf"),
+        }
+    }
+}
+
+impl FromStr for Font {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "block" => Ok(Font::Block),
+            "slant" => Ok(Font::Slant),
+            _ => Err(CommandError::InvalidArgument {
+                argument: "font".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// A parsed FIGlet font.
+struct FigFont {
+    /// The character used in glyphs for spaces that shouldn't be removed by
+    /// kerning.
+    hardblank: char,
+    /// The rows of each glyph in the font.
+    glyphs: HashMap<char, Vec<Vec<char>>>,
+}
+
+impl FigFont {
+    /// Parse the contents of a FIGlet font file, returning `None` if it isn't
+    /// valid.
+    fn parse(data: &str) -> Option<Self> {
+        let mut lines = data.lines();
+        let header = lines.next()?;
+        let mut params = header.strip_prefix("flf2a")?.split_whitespace();
+
+        let hardblank = params.next()?.chars().next()?;
+        let height = params.next()?.parse::<usize>().ok()?;
+        let comment_lines = params.nth(3)?.parse::<usize>().ok()?;
+
+        let mut lines = lines.skip(comment_lines);
+        let mut glyphs = HashMap::new();
+
+        for c in (32..127).map(char::from) {
+            let rows = (0..height)
+                .map(|_| {
+                    // Each row ends with one or more copies of an end mark,
+                    // which is usually `@`.
+                    let line = lines.next()?;
+                    let end_mark = line.chars().last()?;
+
+                    Some(line.trim_end_matches(end_mark).chars().collect())
+                })
+                .collect::<Option<Vec<_>>>()?;
+
+            glyphs.insert(c, rows);
+        }
+
+        Some(FigFont { hardblank, glyphs })
+    }
+
+    /// Render a single line of text, returning the rows of the output.
+    ///
+    /// Each glyph is kerned, by moving it left until it touches the previous
+    /// one. Characters that aren't in the font are rendered as `?`.
+    fn render(&self, text: &str) -> Vec<Vec<char>> {
+        let mut rows: Vec<Vec<char>> = Vec::new();
+
+        for c in text.chars() {
+            let glyph = match self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?')) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+
+            if rows.is_empty() {
+                rows = vec![Vec::new(); glyph.len()];
+            }
+
+            let shift = rows
+                .iter()
+                .zip(glyph)
+                .map(|(row, glyph_row)| {
+                    let trailing = row.iter().rev().take_while(|&&c| c == ' ').count();
+                    let leading = glyph_row.iter().take_while(|&&c| c == ' ').count();
+
+                    trailing + leading
+                })
+                .min()
+                .unwrap_or(0);
+
+            for (row, glyph_row) in rows.iter_mut().zip(glyph) {
+                let trailing = row.iter().rev().take_while(|&&c| c == ' ').count();
+                let from_row = trailing.min(shift);
+
+                row.truncate(row.len() - from_row);
+                row.extend(glyph_row.iter().skip(shift - from_row));
+            }
+        }
+
+        rows
+    }
+}
+
+#[instrument]
+pub fn banner(input: String, font: Font) -> Result<Response, CommandError> {
+    let fig_font = FigFont::parse(font.data())
+        .ok_or_else(|| CommandError::Internal(format!("failed to parse font {:?}", font)))?;
+
+    // The fonts only cover ASCII, so accented and decorated letters are
+    // rendered as the plain letters they're based on.
+    let banners = fold_text(&input)
+        .lines()
+        .flat_map(|line| wrap(&fig_font, line))
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| {
+                    let row = row.into_iter().collect::<String>();
+                    row.replace(fig_font.hardblank, " ").trim_end().to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>();
+
+    let output = format!("```\n{}\n```", banners.join("\n\n"));
+    let response = Response::Banner {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// Render a line of text, wrapping it between words so that each rendered line
+/// is at most [`MAX_WIDTH`] columns wide, where possible.
+fn wrap(font: &FigFont, line: &str) -> Vec<Vec<Vec<char>>> {
+    let mut rendered = Vec::new();
+    let mut current = String::new();
+
+    for word in line.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", current, word)
+        };
+
+        let width = font
+            .render(&candidate)
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0);
+
+        if width > MAX_WIDTH && !current.is_empty() {
+            rendered.push(font.render(&current));
+            current = word.to_string();
+        } else {
+            current = candidate;
+        }
+    }
+
+    if !current.is_empty() {
+        rendered.push(font.render(&current));
+    }
+
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONTS: &[Font] = &[Font::Block, Font::Slant];
+
+    fn output(input: &str, font: Font) -> String {
+        match banner(input.to_string(), font) {
+            Ok(Response::Banner { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn bundled_fonts_parse() {
+        for &font in FONTS {
+            let fig_font = FigFont::parse(font.data()).expect("font should parse");
+
+            assert_eq!(fig_font.glyphs.len(), 95, "{:?}", font);
+        }
+    }
+
+    #[test]
+    fn output_is_a_code_block_without_hardblanks() {
+        for &font in FONTS {
+            let fig_font = FigFont::parse(font.data()).unwrap();
+            let output = output("Hi there", font);
+
+            assert!(output.starts_with("```\n") && output.ends_with("\n```"));
+            assert!(!output.contains(fig_font.hardblank), "{:?}", font);
+        }
+    }
+
+    #[test]
+    fn long_lines_are_wrapped_between_words() {
+        for &font in FONTS {
+            let fig_font = FigFont::parse(font.data()).unwrap();
+            let rendered = wrap(&fig_font, "the quick brown fox jumps over the lazy dog");
+
+            assert!(rendered.len() > 1, "{:?}", font);
+
+            for rows in rendered {
+                assert!(rows.iter().all(|row| row.len() <= MAX_WIDTH), "{:?}", font);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_characters_are_rendered_as_question_marks() {
+        let fig_font = FigFont::parse(Font::Block.data()).unwrap();
+
+        assert_eq!(fig_font.render("€"), fig_font.render("?"));
+    }
+
+    #[test]
+    fn accents_are_folded() {
+        assert_eq!(output("café", Font::Block), output("cafe", Font::Block));
+    }
+}
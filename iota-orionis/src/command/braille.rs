@@ -0,0 +1,150 @@
+//! Convert text to Grade 1 (uncontracted) Unified English Braille.
+
+use super::{check_length, normalize::fold_text, CommandError, Response};
+
+/// Cells for the letters a to z.
+#[rustfmt::skip]
+const LETTERS: [char; 26] = [
+    '⠁', '⠃', '⠉', '⠙', '⠑', '⠋', '⠛', '⠓', '⠊', '⠚', '⠅', '⠇', '⠍',
+    '⠝', '⠕', '⠏', '⠟', '⠗', '⠎', '⠞', '⠥', '⠧', '⠺', '⠭', '⠽', '⠵',
+];
+
+/// Cells for punctuation and symbols.
+#[rustfmt::skip]
+const SYMBOLS: &[(char, &str)] = &[
+    (',', "⠂"), (';', "⠆"), (':', "⠒"), ('.', "⠲"), ('!', "⠖"), ('?', "⠦"), ('\'', "⠄"),
+    ('-', "⠤"), ('"', "⠠⠶"), ('(', "⠐⠣"), (')', "⠐⠜"), ('/', "⠸⠌"), ('&', "⠈⠯"),
+    ('@', "⠈⠁"), ('*', "⠐⠔"), ('#', "⠸⠹"), ('+', "⠐⠖"), ('=', "⠐⠶"), ('%', "⠨⠴"),
+    ('$', "⠈⠎"),
+];
+
+/// Shows that the next letter is a capital.
+const CAPITAL: char = '⠠';
+
+/// Shows that the following digits are a number, written with the cells for a
+/// to j.
+const NUMERIC: char = '⠼';
+
+/// Shows that the next cell is a letter rather than a digit, after a number.
+const GRADE_1: char = '⠰';
+
+#[instrument]
+pub fn braille(input: String) -> Result<Response, CommandError> {
+    let output = brailleify(&fold_text(&input));
+    let response = Response::Braille {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// Convert text to braille, word by word. Characters without a braille
+/// equivalent are left as-is.
+fn brailleify(input: &str) -> String {
+    let mut output = String::new();
+    let mut word = String::new();
+
+    for c in input.chars() {
+        if c.is_whitespace() {
+            output.push_str(&convert_word(&word));
+            output.push(c);
+            word.clear();
+        } else {
+            word.push(c);
+        }
+    }
+
+    output.push_str(&convert_word(&word));
+    output
+}
+
+/// Convert a single word to braille. A word that's entirely capitals (with at
+/// least two letters) gets a double capital sign at the start instead of one
+/// before every letter.
+fn convert_word(word: &str) -> String {
+    let letters = word.chars().filter(|c| c.is_ascii_alphabetic()).count();
+    let all_caps = letters >= 2 && !word.chars().any(|c| c.is_ascii_lowercase());
+
+    let mut output = String::new();
+    let mut in_number = false;
+
+    if all_caps {
+        output.push(CAPITAL);
+        output.push(CAPITAL);
+    }
+
+    for c in word.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            if !in_number {
+                output.push(NUMERIC);
+                in_number = true;
+            }
+
+            // The digits 1 to 9 use the cells for a to i, and 0 uses j.
+            output.push(LETTERS[(digit as usize + 9) % 10]);
+            continue;
+        }
+
+        if c.is_ascii_alphabetic() {
+            let lower = c.to_ascii_lowercase();
+
+            // Right after a number, the letters a to j would be read as digits.
+            if in_number && ('a'..='j').contains(&lower) {
+                output.push(GRADE_1);
+            }
+
+            if c.is_ascii_uppercase() && !all_caps {
+                output.push(CAPITAL);
+            }
+
+            output.push(LETTERS[(lower as u8 - b'a') as usize]);
+        } else {
+            match SYMBOLS.iter().find(|&&(symbol, _)| symbol == c) {
+                Some(&(_, cells)) => output.push_str(cells),
+                None => output.push(c),
+            }
+        }
+
+        in_number = false;
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(input: &str) -> String {
+        match braille(input.to_string()) {
+            Ok(Response::Braille { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn letters_and_punctuation_are_converted() {
+        assert_eq!(output("hello, world!"), "⠓⠑⠇⠇⠕⠂ ⠺⠕⠗⠇⠙⠖");
+    }
+
+    #[test]
+    fn capitals_are_marked() {
+        assert_eq!(output("Hi"), "⠠⠓⠊");
+        assert_eq!(output("NASA"), "⠠⠠⠝⠁⠎⠁");
+        assert_eq!(output("I"), "⠠⠊");
+    }
+
+    #[test]
+    fn numbers_are_marked() {
+        assert_eq!(output("2023"), "⠼⠃⠚⠃⠉");
+        assert_eq!(output("1st"), "⠼⠁⠎⠞");
+        assert_eq!(output("2a"), "⠼⠃⠰⠁");
+    }
+
+    #[test]
+    fn accents_are_folded() {
+        assert_eq!(output("café"), output("cafe"));
+    }
+}
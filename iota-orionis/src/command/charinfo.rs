@@ -0,0 +1,191 @@
+//! Describe each codepoint in some text, using Unicode data bundled into the
+//! binary so that it works without any network access.
+
+use unicode_general_category::{get_general_category, GeneralCategory};
+
+use super::{CommandError, Response};
+
+/// The number of codepoints to describe on each page.
+const CHARS_PER_PAGE: usize = 20;
+
+/// The maximum number of pages to respond with, so that a long input doesn't
+/// flood the channel.
+const MAX_PAGES: usize = 5;
+
+/// Information about a single codepoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharInfo {
+    /// The codepoint itself.
+    pub codepoint: char,
+    /// The codepoint's name, or a label in angle brackets (like `<control>`)
+    /// if it doesn't have one.
+    pub name: String,
+    /// The two-letter abbreviation of the codepoint's general category, like
+    /// `Lu`.
+    pub category: &'static str,
+    /// The full name of the codepoint's general category, like `Uppercase
+    /// Letter`.
+    pub category_name: &'static str,
+}
+
+impl CharInfo {
+    /// Look up the information about a codepoint.
+    pub fn new(codepoint: char) -> Self {
+        let general_category = get_general_category(codepoint);
+        let (category, category_name) = category_names(general_category);
+
+        let name = unicode_names2::name(codepoint)
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| {
+                match general_category {
+                    GeneralCategory::Control => "<control>",
+                    GeneralCategory::PrivateUse => "<private use>",
+                    GeneralCategory::Unassigned => "<unassigned>",
+                    _ => "<unknown>",
+                }
+                .to_string()
+            });
+
+        CharInfo {
+            codepoint,
+            name,
+            category,
+            category_name,
+        }
+    }
+
+    /// The codepoint formatted as a U+ value, like `U+0041`.
+    pub fn code(&self) -> String {
+        format!("U+{:04X}", self.codepoint as u32)
+    }
+
+    /// A visible representation of the codepoint, if it has one.
+    ///
+    /// Combining marks are shown on a dotted circle, since they have nothing
+    /// else to attach to, and nothing is returned for invisible codepoints like
+    /// control characters and spaces.
+    pub fn display(&self) -> Option<String> {
+        match self.category {
+            "Mn" | "Mc" | "Me" => Some(format!("\u{25cc}{}", self.codepoint)),
+            "Cc" | "Cf" | "Cn" | "Co" | "Cs" | "Zs" | "Zl" | "Zp" => None,
+            _ => Some(self.codepoint.to_string()),
+        }
+    }
+}
+
+#[instrument]
+pub fn charinfo(input: String) -> Result<Response, CommandError> {
+    // There would be no pages to show, and an empty embed can't be sent.
+    if input.is_empty() {
+        warn!("input is empty");
+
+        return Err(CommandError::EmptyInput);
+    }
+
+    let length = input.chars().count();
+    let max_chars = CHARS_PER_PAGE * MAX_PAGES;
+
+    if length > max_chars {
+        warn!("input has too many codepoints to describe");
+
+        return Err(CommandError::TooLong { length, max_chars });
+    }
+
+    let chars = input.chars().map(CharInfo::new).collect::<Vec<_>>();
+
+    let response = Response::CharInfo {
+        pages: chars
+            .chunks(CHARS_PER_PAGE)
+            .map(|page| page.to_vec())
+            .collect(),
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// The abbreviation and full name of a general category.
+fn category_names(category: GeneralCategory) -> (&'static str, &'static str) {
+    match category {
+        GeneralCategory::UppercaseLetter => ("Lu", "Uppercase Letter"),
+        GeneralCategory::LowercaseLetter => ("Ll", "Lowercase Letter"),
+        GeneralCategory::TitlecaseLetter => ("Lt", "Titlecase Letter"),
+        GeneralCategory::ModifierLetter => ("Lm", "Modifier Letter"),
+        GeneralCategory::OtherLetter => ("Lo", "Other Letter"),
+        GeneralCategory::NonspacingMark => ("Mn", "Nonspacing Mark"),
+        GeneralCategory::SpacingMark => ("Mc", "Spacing Mark"),
+        GeneralCategory::EnclosingMark => ("Me", "Enclosing Mark"),
+        GeneralCategory::DecimalNumber => ("Nd", "Decimal Number"),
+        GeneralCategory::LetterNumber => ("Nl", "Letter Number"),
+        GeneralCategory::OtherNumber => ("No", "Other Number"),
+        GeneralCategory::ConnectorPunctuation => ("Pc", "Connector Punctuation"),
+        GeneralCategory::DashPunctuation => ("Pd", "Dash Punctuation"),
+        GeneralCategory::OpenPunctuation => ("Ps", "Open Punctuation"),
+        GeneralCategory::ClosePunctuation => ("Pe", "Close Punctuation"),
+        GeneralCategory::InitialPunctuation => ("Pi", "Initial Punctuation"),
+        GeneralCategory::FinalPunctuation => ("Pf", "Final Punctuation"),
+        GeneralCategory::OtherPunctuation => ("Po", "Other Punctuation"),
+        GeneralCategory::MathSymbol => ("Sm", "Math Symbol"),
+        GeneralCategory::CurrencySymbol => ("Sc", "Currency Symbol"),
+        GeneralCategory::ModifierSymbol => ("Sk", "Modifier Symbol"),
+        GeneralCategory::OtherSymbol => ("So", "Other Symbol"),
+        GeneralCategory::SpaceSeparator => ("Zs", "Space Separator"),
+        GeneralCategory::LineSeparator => ("Zl", "Line Separator"),
+        GeneralCategory::ParagraphSeparator => ("Zp", "Paragraph Separator"),
+        GeneralCategory::Control => ("Cc", "Control"),
+        GeneralCategory::Format => ("Cf", "Format"),
+        GeneralCategory::Surrogate => ("Cs", "Surrogate"),
+        GeneralCategory::PrivateUse => ("Co", "Private Use"),
+        GeneralCategory::Unassigned => ("Cn", "Unassigned"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(input: &str) -> Vec<Vec<CharInfo>> {
+        match charinfo(input.to_string()) {
+            Ok(Response::CharInfo { pages }) => pages,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn codepoints_are_described() {
+        let pages = pages("A\u{301} ");
+        let chars = &pages[0];
+
+        assert_eq!(chars[0].code(), "U+0041");
+        assert_eq!(chars[0].name, "LATIN CAPITAL LETTER A");
+        assert_eq!(chars[0].display().as_deref(), Some("A"));
+        assert_eq!(chars[1].category, "Mn");
+        assert_eq!(chars[1].display().as_deref(), Some("\u{25cc}\u{301}"));
+        assert_eq!(chars[2].display(), None);
+    }
+
+    #[test]
+    fn long_input_is_split_into_pages() {
+        let pages = pages(&"a".repeat(CHARS_PER_PAGE + 1));
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].len(), 1);
+    }
+
+    #[test]
+    fn empty_input_is_rejected() {
+        assert!(matches!(
+            charinfo(String::new()),
+            Err(CommandError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn too_many_codepoints_are_rejected() {
+        assert!(matches!(
+            charinfo("a".repeat(CHARS_PER_PAGE * MAX_PAGES + 1)),
+            Err(CommandError::TooLong { .. })
+        ));
+    }
+}
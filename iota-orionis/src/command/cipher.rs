@@ -0,0 +1,262 @@
+//! Encrypt and decrypt text with classical ciphers.
+//!
+//! All of these ciphers only change the letters A to Z, keeping their case, and
+//! leave everything else (including Discord markup, like mentions and links)
+//! alone.
+
+use std::str::FromStr;
+
+use super::{
+    markup::{self, Segment},
+    CommandError, Response,
+};
+
+/// The longest input that can be brute forced, so that all 25 shifts fit in a
+/// single embed.
+const MAX_BRUTEFORCE_LENGTH: usize = 200;
+
+/// Whether to encrypt or decrypt the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Encrypt plain text.
+    Encode,
+    /// Decrypt encrypted text.
+    Decode,
+}
+
+impl FromStr for Operation {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "encode" | "encrypt" => Ok(Operation::Encode),
+            "decode" | "decrypt" => Ok(Operation::Decode),
+            _ => Err(CommandError::InvalidArgument {
+                argument: "operation".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[instrument]
+pub fn rot13(input: String) -> Response {
+    let response = Response::Rot13 {
+        output: map_letters(&input, |offset| (offset + 13) % 26),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+#[instrument]
+pub fn caesar(input: String, shift: i64) -> Response {
+    let response = Response::Caesar {
+        output: caesar_shift(&input, shift),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+#[instrument]
+pub fn caesar_bruteforce(input: String) -> Result<Response, CommandError> {
+    // Every shift is shown in its own embed field, and Discord rejects fields
+    // without any visible text.
+    if input.trim().is_empty() {
+        warn!("input is empty");
+
+        return Err(CommandError::EmptyInput);
+    }
+
+    let length = input.chars().count();
+
+    if length > MAX_BRUTEFORCE_LENGTH {
+        warn!("input is too long to brute force");
+
+        return Err(CommandError::TooLong {
+            length,
+            max_chars: MAX_BRUTEFORCE_LENGTH,
+        });
+    }
+
+    let response = Response::CaesarBruteforce {
+        shifts: (1..26)
+            .map(|shift| (shift as u8, caesar_shift(&input, shift)))
+            .collect(),
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+#[instrument]
+pub fn atbash(input: String) -> Response {
+    let response = Response::Atbash {
+        output: map_letters(&input, |offset| 25 - offset),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+#[instrument]
+pub fn vigenere(
+    input: String,
+    key: String,
+    operation: Operation,
+) -> Result<Response, CommandError> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphabetic()) {
+        warn!("key contains characters other than letters");
+
+        return Err(CommandError::InvalidArgument {
+            argument: "key".to_string(),
+            value: key,
+        });
+    }
+
+    let shifts = key
+        .bytes()
+        .map(|b| b.to_ascii_lowercase() - b'a')
+        .collect::<Vec<_>>();
+
+    // The key only advances on letters, so that spaces and punctuation in the
+    // input don't change how the letters around them are encrypted.
+    let mut shifts = shifts.iter().cycle();
+
+    let output = map_letters(&input, |offset| {
+        let shift = shifts.next().copied().unwrap_or(0);
+
+        match operation {
+            Operation::Encode => (offset + shift) % 26,
+            Operation::Decode => (offset + 26 - shift) % 26,
+        }
+    });
+
+    let response = Response::Vigenere { output };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// Shift every letter in the input forward by `shift` places, wrapping around
+/// from Z to A. Negative shifts move letters backwards.
+fn caesar_shift(input: &str, shift: i64) -> String {
+    let shift = shift.rem_euclid(26) as u8;
+
+    map_letters(input, |offset| (offset + shift) % 26)
+}
+
+/// Replace every ASCII letter outside of Discord markup, by passing its offset
+/// from A (from 0 to 25) to `map` and using the letter at the returned offset,
+/// in the same case as the original.
+fn map_letters<F: FnMut(u8) -> u8>(input: &str, mut map: F) -> String {
+    markup::segments(input)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_lowercase() {
+                        (b'a' + map(c as u8 - b'a')) as char
+                    } else if c.is_ascii_uppercase() {
+                        (b'A' + map(c as u8 - b'A')) as char
+                    } else {
+                        c
+                    }
+                })
+                .collect(),
+            Segment::Verbatim(verbatim) => verbatim.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vigenere_output(input: &str, key: &str, operation: Operation) -> String {
+        match vigenere(input.to_string(), key.to_string(), operation) {
+            Ok(Response::Vigenere { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn vigenere_matches_the_classic_example() {
+        assert_eq!(
+            vigenere_output("Attack at dawn", "LEMON", Operation::Encode),
+            "Lxfopv ef rnhr"
+        );
+        assert_eq!(
+            vigenere_output("Lxfopv ef rnhr", "lemon", Operation::Decode),
+            "Attack at dawn"
+        );
+    }
+
+    #[test]
+    fn vigenere_rejects_keys_without_only_letters() {
+        for key in ["", "lem0n", "le mon"] {
+            assert!(matches!(
+                vigenere("text".to_string(), key.to_string(), Operation::Encode),
+                Err(CommandError::InvalidArgument { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn caesar_shifts_wrap_in_both_directions() {
+        assert_eq!(caesar_shift("Hello, World!", 3), "Khoor, Zruog!");
+        assert_eq!(caesar_shift("abc", -1), "zab");
+        assert_eq!(caesar_shift("xyz", 29), "abc");
+    }
+
+    #[test]
+    fn rot13_and_atbash_undo_themselves() {
+        for input in ["Hello, World!", "The quick brown fox"] {
+            match rot13(input.to_string()) {
+                Response::Rot13 { output } => {
+                    assert_eq!(map_letters(&output, |offset| (offset + 13) % 26), input)
+                }
+                response => panic!("unexpected response {:?}", response),
+            }
+
+            match atbash(input.to_string()) {
+                Response::Atbash { output } => {
+                    assert_eq!(map_letters(&output, |offset| 25 - offset), input)
+                }
+                response => panic!("unexpected response {:?}", response),
+            }
+        }
+    }
+
+    #[test]
+    fn markup_is_left_alone() {
+        assert_eq!(caesar_shift("abc `abc` <@123>", 1), "bcd `abc` <@123>");
+    }
+
+    #[test]
+    fn bruteforce_rejects_empty_input() {
+        assert!(matches!(
+            caesar_bruteforce("  ".to_string()),
+            Err(CommandError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn bruteforce_includes_every_shift() {
+        match caesar_bruteforce("abc".to_string()) {
+            Ok(Response::CaesarBruteforce { shifts }) => {
+                assert_eq!(shifts.len(), 25);
+                assert_eq!(shifts[0], (1, "bcd".to_string()));
+                assert_eq!(shifts[24], (25, "zab".to_string()));
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
@@ -1,29 +1,97 @@
-//! Insert clapping emojis between every word of the input text.
+//! Insert clapping emojis (or another separator) between every word of the
+//! input text.
 
-use super::Response;
+use super::{check_length, react, CommandError, Response};
+
+/// The separator used if no other is given.
+pub const DEFAULT_SEPARATOR: &str = "👏";
 
 #[instrument]
-pub fn clap(input: String) -> Response {
-    let mut words = input.split(' ');
-
-    let clappified = words
-        .next()
-        .map(|first| first.to_string())
-        .map(|first| {
-            words.fold(first, |mut acc, next| {
-                acc.push_str(&format!(" 👏 {}", next));
-                acc
-            })
-        })
-        .map(|mut output| {
-            output.push_str(" 👏");
-            output
+pub fn clap(input: String, separator: String, trailing: bool) -> Result<Response, CommandError> {
+    let emoji = react::parse_emoji(&separator).ok_or_else(|| {
+        warn!("separator is not an emoji");
+
+        CommandError::InvalidArgument {
+            argument: "--with".to_string(),
+            value: separator.clone(),
+        }
+    })?;
+
+    // Each line is clappified separately, so that line breaks in the input are
+    // kept in the output.
+    let clappified = input
+        .lines()
+        .map(|line| {
+            let mut words = line.split_whitespace();
+
+            words
+                .next()
+                .map(|first| first.to_string())
+                .map(|first| {
+                    words.fold(first, |mut acc, next| {
+                        acc.push_str(&format!(" {} {}", separator, next));
+                        acc
+                    })
+                })
+                .map(|mut output| {
+                    if trailing {
+                        output.push_str(&format!(" {}", separator));
+                    }
+
+                    output
+                })
+                .unwrap_or_else(String::new)
         })
-        .unwrap_or_else(String::new);
+        .collect::<Vec<String>>()
+        .join("\n");
 
-    let response = Response::Clap { output: clappified };
+    let response = Response::Clap {
+        output: check_length(clappified)?,
+        emoji,
+    };
 
     debug!(?response);
 
-    response
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clapped(input: &str, separator: &str, trailing: bool) -> String {
+        match clap(input.to_string(), separator.to_string(), trailing) {
+            Ok(Response::Clap { output, .. }) => output,
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn separator_goes_between_words() {
+        assert_eq!(clapped("a  b c", "👏", false), "a 👏 b 👏 c");
+        assert_eq!(clapped("a b\nc", "👏", true), "a 👏 b 👏\nc 👏");
+    }
+
+    #[test]
+    fn custom_emoji_separator_is_accepted() {
+        assert_eq!(clapped("a b", "<:yes:123>", false), "a <:yes:123> b");
+    }
+
+    #[test]
+    fn separator_must_be_an_emoji() {
+        for separator in ["", "and", "👏👏", "<:yes:123> and"] {
+            assert!(matches!(
+                clap("a b".to_string(), separator.to_string(), false),
+                Err(CommandError::InvalidArgument { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn long_output_is_rejected() {
+        assert!(matches!(
+            clap("a ".repeat(600), "<:yes:123>".to_string(), false),
+            Err(CommandError::TooLong { .. })
+        ));
+    }
 }
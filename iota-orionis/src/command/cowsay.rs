@@ -0,0 +1,205 @@
+//! Have a cow (or another character) say or think the input, in a speech or
+//! thought bubble.
+//!
+//! The characters come from a bundled set of cow files, in the same format as
+//! the original `cowsay`.
+
+use super::{check_length, CommandError, Response};
+
+/// The cow used if no other is given.
+pub const DEFAULT_COW: &str = "default";
+
+/// The width the text in the bubble is wrapped to if no other is given.
+pub const DEFAULT_WIDTH: usize = 40;
+
+/// The narrowest and widest the text in the bubble can be wrapped to.
+const WIDTHS: (usize, usize) = (10, 76);
+
+/// The bundled cow files.
+const COWS: &[(&str, &str)] = &[
+    ("bunny", include_str!("cowsay/bunny.cow")),
+    ("default", include_str!("cowsay/default.cow")),
+    ("sheep", include_str!("cowsay/sheep.cow")),
+    ("small", include_str!("cowsay/small.cow")),
+    ("tux", include_str!("cowsay/tux.cow")),
+];
+
+#[instrument]
+pub fn cowsay(
+    input: String,
+    cow: String,
+    width: usize,
+    think: bool,
+) -> Result<Response, CommandError> {
+    let (min_width, max_width) = WIDTHS;
+
+    if width < min_width || width > max_width {
+        warn!("invalid bubble width");
+
+        return Err(CommandError::InvalidArgument {
+            argument: "--width".to_string(),
+            value: width.to_string(),
+        });
+    }
+
+    let cow_file = COWS
+        .iter()
+        .find(|&&(name, _)| name == cow.to_lowercase())
+        .map(|&(_, cow_file)| cow_file)
+        .ok_or_else(|| {
+            warn!("unknown cow");
+
+            CommandError::InvalidArgument {
+                argument: "--cow".to_string(),
+                value: cow.clone(),
+            }
+        })?;
+
+    let thoughts = if think { "o" } else { "\\" };
+    let body = format!(
+        "{}\n{}",
+        bubble(&wrap(&input, width), think),
+        draw_cow(cow_file, thoughts)
+    );
+
+    let output = format!("```\n{}\n```", escape_backticks(&body));
+    let response = Response::Cowsay {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// Wrap the input to lines at most `width` characters long, breaking between
+/// words where possible. Line breaks in the input are kept.
+fn wrap(input: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in input.replace('\t', "    ").lines() {
+        let mut line = String::new();
+
+        for word in paragraph.split_whitespace() {
+            let word = word.chars().collect::<Vec<_>>();
+            let length = line.chars().count();
+
+            if length > 0 && length + 1 + word.len() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+
+            // Words that are too long to fit on a line of their own are split,
+            // with a full line pushed only while more of the word remains, so
+            // the last piece (which may fill a whole line) starts the next one.
+            let mut rest = &word[..];
+
+            while rest.len() > width {
+                let (piece, remaining) = rest.split_at(width);
+                lines.push(piece.iter().collect());
+                rest = remaining;
+            }
+
+            if !line.is_empty() {
+                line.push(' ');
+            }
+
+            line.extend(rest);
+        }
+
+        lines.push(line);
+    }
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    lines
+}
+
+/// Draw a speech or thought bubble around the lines.
+fn bubble(lines: &[String], think: bool) -> String {
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let count = lines.len();
+
+    let mut output = vec![format!(" {}", "_".repeat(width + 2))];
+
+    for (i, line) in lines.iter().enumerate() {
+        let (left, right) = if think {
+            ('(', ')')
+        } else if count == 1 {
+            ('<', '>')
+        } else if i == 0 {
+            ('/', '\\')
+        } else if i == count - 1 {
+            ('\\', '/')
+        } else {
+            ('|', '|')
+        };
+
+        let padding = " ".repeat(width - line.chars().count());
+        output.push(format!("{} {}{} {}", left, line, padding, right));
+    }
+
+    output.push(format!(" {}", "-".repeat(width + 2)));
+    output.join("\n")
+}
+
+/// Draw a cow from a cow file, with the given characters leading up to the
+/// bubble.
+///
+/// Cow files are Perl scripts, but only the `$the_cow` heredoc is used here,
+/// with its variables and escapes replaced by hand.
+fn draw_cow(cow_file: &str, thoughts: &str) -> String {
+    cow_file
+        .lines()
+        .skip_while(|line| !line.starts_with("$the_cow"))
+        .skip(1)
+        .take_while(|&line| line != "EOC")
+        .map(|line| {
+            line.replace("\\\\", "\\")
+                .replace("\\@", "@")
+                .replace("\\$", "$")
+                .replace("$thoughts", thoughts)
+                .replace("$eyes", "oo")
+                .replace("$tongue", "  ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Add a zero width space between every pair of adjacent backticks, so that the
+/// text can't end the code block it's in early.
+fn escape_backticks(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        if c == '`' && escaped.ends_with('`') {
+            escaped.push('\u{200b}');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_splits_long_words() {
+        assert_eq!(wrap("abcdefghijklm", 5), vec!["abcde", "fghij", "klm"]);
+    }
+
+    #[test]
+    fn wrap_leaves_no_empty_line_after_exact_multiple() {
+        assert_eq!(wrap("abcdefghij", 5), vec!["abcde", "fghij"]);
+        assert_eq!(wrap("abcdefghij x", 5), vec!["abcde", "fghij", "x"]);
+        assert_eq!(wrap("x abcdefghij", 5), vec!["x", "abcde", "fghij"]);
+    }
+}
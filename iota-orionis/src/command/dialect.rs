@@ -0,0 +1,271 @@
+//! Translate text into silly dialects, like pirate speak, using bundled tables
+//! of phrases and words to substitute.
+
+use std::str::FromStr;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{
+    check_length,
+    markup::{self, Segment},
+    CommandError, Response,
+};
+
+/// Pirate phrases and words.
+#[rustfmt::skip]
+const PIRATE: &[(&str, &str)] = &[
+    ("am", "be"), ("are", "be"), ("bathroom", "head"), ("beer", "grog"), ("boy", "lad"),
+    ("cheat", "hornswaggle"), ("do you", "d'ye"), ("drink", "grog"), ("everyone", "all hands"),
+    ("excuse me", "arrr"), ("food", "grub"), ("for", "fer"), ("friend", "matey"),
+    ("friends", "mateys"), ("girl", "lass"), ("hello", "ahoy"), ("hey", "ahoy"), ("hi", "ahoy"),
+    ("how are you", "how be ye"), ("i am", "i be"), ("idiot", "bilge rat"), ("is", "be"),
+    ("isn't", "be not"), ("kitchen", "galley"), ("look", "spy"), ("money", "doubloons"),
+    ("my", "me"), ("my friend", "me hearty"), ("no", "nay"), ("of", "o'"), ("stop", "avast"),
+    ("stupid", "addled"), ("the", "th'"), ("there", "thar"), ("treasure", "booty"), ("wow", "blimey"),
+    ("yes", "aye"), ("you", "ye"), ("you are", "ye be"), ("you're", "ye be"), ("your", "yer"),
+];
+
+/// Shakespearean phrases and words.
+#[rustfmt::skip]
+const SHAKESPEARE: &[(&str, &str)] = &[
+    ("are you", "art thou"), ("before", "ere"), ("between", "betwixt"), ("boy", "youth"),
+    ("bye", "adieu"), ("does", "doth"), ("ever", "e'er"), ("friend", "coz"), ("girl", "maiden"),
+    ("goodbye", "fare thee well"), ("has", "hath"), ("hello", "good morrow"), ("here", "hither"),
+    ("hi", "hail"), ("i think", "methinks"), ("idiot", "knave"), ("it is", "'tis"),
+    ("it was", "'twas"), ("it's", "'tis"), ("listen", "hark"), ("look", "behold"),
+    ("maybe", "perchance"), ("money", "coin"), ("never", "ne'er"), ("no", "nay"),
+    ("nothing", "naught"), ("often", "oft"), ("ok", "'tis well"), ("okay", "'tis well"),
+    ("over", "o'er"), ("perhaps", "perchance"), ("please", "prithee"), ("quickly", "apace"),
+    ("soon", "anon"), ("stupid", "beef-witted"), ("thank you", "gramercy"), ("thanks", "gramercy"),
+    ("there", "thither"), ("very", "most"), ("where", "whither"), ("why", "wherefore"),
+    ("yes", "aye"), ("you", "thou"), ("you are", "thou art"), ("you're", "thou art"),
+    ("your", "thy"), ("yours", "thine"), ("yourself", "thyself"),
+];
+
+/// Cowboy phrases and words.
+#[rustfmt::skip]
+const COWBOY: &[(&str, &str)] = &[
+    ("are not", "ain't"), ("aren't", "ain't"), ("car", "horse"), ("drink", "sarsaparilla"),
+    ("food", "grub"), ("friend", "partner"), ("friends", "pardners"), ("going to", "fixin' to"),
+    ("hello", "howdy"), ("hey", "howdy"), ("hi", "howdy"), ("house", "ranch"), ("is not", "ain't"),
+    ("isn't", "ain't"), ("money", "dough"), ("no", "nope"), ("really", "real"), ("very", "mighty"),
+    ("yes", "yep"), ("you all", "y'all"), ("you guys", "y'all"),
+];
+
+/// A dialect to translate text into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// Ahoy, me hearty!
+    Pirate,
+    /// Good morrow, coz!
+    Shakespeare,
+    /// Howdy, partner!
+    Cowboy,
+}
+
+impl Dialect {
+    /// The phrases and words to substitute, in lowercase.
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Dialect::Pirate => PIRATE,
+            Dialect::Shakespeare => SHAKESPEARE,
+            Dialect::Cowboy => COWBOY,
+        }
+    }
+
+    /// Word endings to replace in any words that aren't in the table.
+    fn suffixes(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Dialect::Pirate | Dialect::Cowboy => &[("ing", "in'")],
+            Dialect::Shakespeare => &[],
+        }
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "pirate" => Ok(Dialect::Pirate),
+            "shakespeare" => Ok(Dialect::Shakespeare),
+            "cowboy" => Ok(Dialect::Cowboy),
+            _ => Err(CommandError::InvalidArgument {
+                argument: "dialect".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[instrument]
+pub fn dialect(input: String, dialect: Dialect) -> Result<Response, CommandError> {
+    let output = markup::segments(&input)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => translate(text, dialect),
+            Segment::Verbatim(verbatim) => verbatim.to_string(),
+        })
+        .collect::<String>();
+
+    // Some substitutions are longer than the words they replace.
+    let response = Response::Dialect {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// Translate a piece of text, replacing the longest phrase that matches at each
+/// word, or just the ending of the word if nothing matches.
+fn translate(text: &str, dialect: Dialect) -> String {
+    let tokens = text.split_word_bounds().collect::<Vec<_>>();
+    let mut output = String::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match longest_match(&tokens[i..], dialect.table()) {
+            Some((len, replacement)) => {
+                output.push_str(&match_case(tokens[i], replacement));
+                i += len;
+            }
+            None => {
+                output.push_str(&replace_suffix(tokens[i], dialect.suffixes()));
+                i += 1;
+            }
+        }
+    }
+
+    output
+}
+
+/// Find the phrase in the table that matches the most words at the start of the
+/// tokens, returning the number of tokens it matches and its replacement.
+///
+/// The words of a phrase must be separated by whitespace in the tokens, but it
+/// doesn't matter how much.
+fn longest_match(tokens: &[&str], table: &[(&str, &'static str)]) -> Option<(usize, &'static str)> {
+    if !tokens.first()?.starts_with(char::is_alphabetic) {
+        return None;
+    }
+
+    table
+        .iter()
+        .filter_map(|&(phrase, replacement)| {
+            let mut len = 0;
+
+            for (n, word) in phrase.split(' ').enumerate() {
+                if n > 0 {
+                    if !tokens.get(len)?.chars().all(char::is_whitespace) {
+                        return None;
+                    }
+
+                    len += 1;
+                }
+
+                // Phones often use a curly apostrophe.
+                if tokens.get(len)?.to_lowercase().replace('’', "'") != word {
+                    return None;
+                }
+
+                len += 1;
+            }
+
+            Some((len, replacement))
+        })
+        .max_by_key(|&(len, _)| len)
+}
+
+/// Replace the ending of a word, if it has one of the suffixes and there's
+/// enough of the word left before it.
+fn replace_suffix(word: &str, suffixes: &[(&str, &str)]) -> String {
+    suffixes
+        .iter()
+        .find_map(|&(suffix, replacement)| {
+            let stem = word.strip_suffix(suffix)?;
+
+            if stem.chars().count() >= 2 && stem.chars().all(char::is_alphabetic) {
+                Some(format!("{}{}", stem, replacement))
+            } else {
+                None
+            }
+        })
+        .unwrap_or_else(|| word.to_string())
+}
+
+/// Change the case of the replacement to match the original word: all capitals
+/// if the word was in all capitals, or starting with a capital if the word
+/// started with one.
+fn match_case(original: &str, replacement: &str) -> String {
+    let letters = original.chars().filter(|c| c.is_alphabetic()).count();
+
+    if letters >= 2 && !original.chars().any(char::is_lowercase) {
+        return replacement.to_uppercase();
+    }
+
+    if !original.starts_with(char::is_uppercase) {
+        return replacement.to_string();
+    }
+
+    // Capitalise the first letter, which might come after an apostrophe.
+    let mut capitalised = String::new();
+    let mut done = false;
+
+    for c in replacement.chars() {
+        if !done && c.is_alphabetic() {
+            capitalised.extend(c.to_uppercase());
+            done = true;
+        } else {
+            capitalised.push(c);
+        }
+    }
+
+    capitalised
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(input: &str, dialect: Dialect) -> String {
+        match super::dialect(input.to_string(), dialect) {
+            Ok(Response::Dialect { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn longest_phrase_is_matched() {
+        assert_eq!(output("my friend", Dialect::Pirate), "me hearty");
+        assert_eq!(output("my  friend", Dialect::Pirate), "me hearty");
+        assert_eq!(output("my cat", Dialect::Pirate), "me cat");
+        assert_eq!(output("you are", Dialect::Shakespeare), "thou art");
+        assert_eq!(output("you’re", Dialect::Shakespeare), "thou art");
+    }
+
+    #[test]
+    fn case_is_matched() {
+        assert_eq!(output("My friend", Dialect::Pirate), "Me hearty");
+        assert_eq!(output("HELLO", Dialect::Cowboy), "HOWDY");
+        assert_eq!(output("It is", Dialect::Shakespeare), "'Tis");
+        assert_eq!(output("I think", Dialect::Shakespeare), "Methinks");
+    }
+
+    #[test]
+    fn suffixes_are_replaced() {
+        assert_eq!(output("singing", Dialect::Pirate), "singin'");
+        assert_eq!(output("Walking", Dialect::Cowboy), "Walkin'");
+        assert_eq!(output("ring", Dialect::Pirate), "ring");
+        assert_eq!(output("singing", Dialect::Shakespeare), "singing");
+    }
+
+    #[test]
+    fn markup_is_left_alone() {
+        assert_eq!(
+            output("hello `hello` <@123> https://hello.com", Dialect::Pirate),
+            "ahoy `hello` <@123> https://hello.com"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Make text look like it was typed while drunk, with typos from hitting
+//! neighbouring keys, doubled letters and missed characters.
+
+use rand::{seq::SliceRandom, Rng};
+
+use super::{
+    markup::{self, Segment},
+    rng, Intensity, Response,
+};
+
+/// The keys next to each letter on a QWERTY keyboard.
+#[rustfmt::skip]
+const ADJACENT: &[(char, &str)] = &[
+    ('q', "wa"), ('w', "qeas"), ('e', "wrsd"), ('r', "etdf"), ('t', "ryfg"), ('y', "tugh"),
+    ('u', "yihj"), ('i', "uojk"), ('o', "ipkl"), ('p', "ol"),
+    ('a', "qwsz"), ('s', "weadzx"), ('d', "erfsxc"), ('f', "rtgdcv"), ('g', "tyhfvb"),
+    ('h', "yujgbn"), ('j', "uikhnm"), ('k', "iolmj"), ('l', "opk"),
+    ('z', "asx"), ('x', "zsdc"), ('c', "xdfv"), ('v', "cfgb"), ('b', "vghn"), ('n', "bhjm"),
+    ('m', "njk"),
+];
+
+/// The chance of hitting a neighbouring key, doubling a letter, and missing a
+/// letter entirely, for each intensity.
+fn chances(intensity: Intensity) -> (f64, f64, f64) {
+    match intensity {
+        Intensity::Mini => (0.03, 0.02, 0.02),
+        Intensity::Normal => (0.06, 0.04, 0.03),
+        Intensity::Max => (0.12, 0.08, 0.06),
+    }
+}
+
+#[instrument]
+pub fn drunk(input: String, intensity: Intensity, seed: Option<u64>) -> Response {
+    let mut rng = rng(seed);
+
+    let output = markup::segments(&input)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => slur(text, intensity, &mut rng),
+            Segment::Verbatim(verbatim) => verbatim.to_string(),
+        })
+        .collect();
+
+    let response = Response::Drunk { output };
+
+    debug!(?response);
+
+    response
+}
+
+/// Add typos to the letters in some text. Only letters on a QWERTY keyboard are
+/// changed, and each one gets at most one typo.
+fn slur<R: Rng>(text: &str, intensity: Intensity, rng: &mut R) -> String {
+    let (typo_chance, double_chance, drop_chance) = chances(intensity);
+
+    let mut output = String::new();
+
+    for c in text.chars() {
+        let adjacent = match ADJACENT
+            .iter()
+            .find(|&&(key, _)| key == c.to_ascii_lowercase())
+        {
+            Some(&(_, adjacent)) => adjacent,
+            None => {
+                output.push(c);
+                continue;
+            }
+        };
+
+        let roll = rng.gen::<f64>();
+
+        if roll < typo_chance {
+            let typo = adjacent
+                .chars()
+                .collect::<Vec<_>>()
+                .choose(rng)
+                .copied()
+                .unwrap_or(c);
+
+            output.push(if c.is_ascii_uppercase() {
+                typo.to_ascii_uppercase()
+            } else {
+                typo
+            });
+        } else if roll < typo_chance + double_chance {
+            output.push(c);
+            output.push(c);
+        } else if roll >= typo_chance + double_chance + drop_chance {
+            output.push(c);
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(input: &str, intensity: Intensity, seed: u64) -> String {
+        match drunk(input.to_string(), intensity, Some(seed)) {
+            Response::Drunk { output } => output,
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_output() {
+        let input = "I'm not drunk, I just had a couple of drinks";
+
+        for seed in 0..10 {
+            assert_eq!(
+                output(input, Intensity::Max, seed),
+                output(input, Intensity::Max, seed)
+            );
+        }
+    }
+
+    #[test]
+    fn some_seed_adds_typos() {
+        let input = "the quick brown fox jumps over the lazy dog";
+
+        assert!((0..10).any(|seed| output(input, Intensity::Max, seed) != input));
+    }
+
+    #[test]
+    fn only_letters_are_changed() {
+        let input = "1234 !? 😀 `code` <@123> https://example.com";
+
+        for seed in 0..10 {
+            assert_eq!(output(input, Intensity::Max, seed), input);
+        }
+    }
+
+    #[test]
+    fn typos_keep_case() {
+        for seed in 0..20 {
+            assert!(output("QWERTY", Intensity::Max, seed)
+                .chars()
+                .all(|c| c.is_ascii_uppercase()));
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Write out text in regional indicator and keycap emojis.
+
+use super::{
+    check_length,
+    markup::{self, Segment},
+    react::to_reactions,
+    CommandError, Response,
+};
+
+#[instrument]
+pub fn emojify(input: String) -> Result<Response, CommandError> {
+    let mut output = String::new();
+
+    for segment in markup::segments(&input) {
+        match segment {
+            Segment::Text(text) => {
+                for c in text.chars() {
+                    if c.is_ascii_alphanumeric() {
+                        // Adjacent regional indicators combine into flags, so
+                        // every emoji is followed by a space. Words end up two
+                        // spaces apart, which keeps them distinguishable.
+                        output.extend(to_reactions(&c.to_string()));
+                        output.push(' ');
+                    } else {
+                        output.push(c);
+                    }
+                }
+            }
+            Segment::Verbatim(verbatim) => output.push_str(verbatim),
+        }
+    }
+
+    let output = output.trim_end().to_string();
+    let response = Response::Emojify {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(input: &str) -> String {
+        match emojify(input.to_string()) {
+            Ok(Response::Emojify { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn letters_and_digits_become_emojis() {
+        assert_eq!(output("Hi 5"), "🇭 🇮  5\u{fe0f}\u{20e3}");
+    }
+
+    #[test]
+    fn other_characters_are_kept() {
+        assert_eq!(output("a, é!"), "🇦 , é!");
+    }
+
+    #[test]
+    fn markup_is_left_alone() {
+        assert_eq!(output("a <@123> `b`"), "🇦  <@123> `b`");
+    }
+
+    #[test]
+    fn long_output_is_rejected() {
+        let result = emojify("a".repeat(1500));
+
+        assert!(matches!(result, Err(CommandError::TooLong { .. })));
+    }
+}
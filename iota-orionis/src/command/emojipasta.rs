@@ -0,0 +1,283 @@
+//! Add relevant emojis after words in the input, using a bundled dictionary of
+//! keywords that can be extended with extra keywords from elsewhere.
+
+use std::collections::HashMap;
+
+use rand::{seq::SliceRandom, Rng};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{
+    check_length,
+    markup::{self, Segment},
+    react, rng, CommandError, Response,
+};
+
+/// Keywords and the emojis that can be added after them.
+#[rustfmt::skip]
+const KEYWORDS: &[(&str, &[&str])] = &[
+    ("angry", &["😠", "😡", "🤬"]), ("apple", &["🍎", "🍏"]), ("baby", &["👶", "🍼"]),
+    ("ball", &["⚽", "🏀", "🏈"]), ("banana", &["🍌"]), ("bear", &["🐻"]), ("beer", &["🍺", "🍻"]),
+    ("bird", &["🐦", "🐤"]), ("birthday", &["🎂", "🎉", "🎁"]), ("book", &["📖", "📚"]),
+    ("boom", &["💥"]), ("boy", &["👦"]), ("bread", &["🍞", "🥖"]), ("brain", &["🧠"]), ("bug", &["🐛", "🐞"]),
+    ("burger", &["🍔"]), ("cake", &["🎂", "🍰"]), ("car", &["🚗", "🚙"]), ("cash", &["💵", "💰"]),
+    ("cat", &["🐱", "🐈"]), ("celebrate", &["🎉", "🥳"]), ("cheese", &["🧀"]),
+    ("chicken", &["🐔", "🍗"]), ("christmas", &["🎄", "🎅"]), ("clap", &["👏"]),
+    ("coffee", &["☕"]), ("computer", &["💻", "🖥️"]), ("cook", &["🍳", "👨‍🍳"]), ("cool", &["😎", "🆒"]),
+    ("cow", &["🐮", "🐄"]), ("crazy", &["🤪"]), ("cry", &["😢", "😭"]), ("dance", &["💃", "🕺"]),
+    ("dead", &["💀", "☠️"]), ("devil", &["😈"]), ("die", &["💀"]), ("dog", &["🐶", "🐕"]),
+    ("dragon", &["🐉", "🐲"]), ("drink", &["🍹", "🥤"]), ("eat", &["🍽️", "😋"]), ("egg", &["🥚", "🍳"]),
+    ("eye", &["👀", "👁️"]), ("face", &["😀"]), ("fast", &["💨", "🏎️"]), ("fire", &["🔥"]),
+    ("fish", &["🐟", "🐠"]), ("flower", &["🌸", "🌹", "🌻"]), ("food", &["🍔", "🍕", "🍟"]),
+    ("football", &["⚽", "🏈"]), ("friend", &["👫", "🤝"]), ("frog", &["🐸"]), ("fun", &["🎉", "😄"]),
+    ("funny", &["😂", "🤣"]), ("game", &["🎮", "🕹️"]), ("ghost", &["👻"]), ("gift", &["🎁"]),
+    ("girl", &["👧", "💁"]), ("good", &["👍", "👌"]), ("guy", &["👨"]),
+    ("hand", &["✋", "🤚"]), ("happy", &["😀", "😊", "😁"]), ("hate", &["😠", "💔"]),
+    ("heart", &["❤️", "💖"]), ("hello", &["👋"]), ("hot", &["🔥", "🥵"]), ("house", &["🏠", "🏡"]),
+    ("hug", &["🤗"]), ("hungry", &["😋", "🍽️"]), ("ice", &["🧊"]), ("idea", &["💡"]),
+    ("joke", &["😂", "🤡"]), ("king", &["👑", "🤴"]), ("kiss", &["😘", "💋"]), ("laugh", &["😂", "🤣"]),
+    ("lol", &["😂", "🤣"]), ("love", &["❤️", "😍", "😘"]), ("magic", &["✨", "🪄"]), ("man", &["👨"]),
+    ("milk", &["🥛"]), ("money", &["💰", "💵", "🤑"]), ("monkey", &["🐒", "🙈"]), ("moon", &["🌙", "🌕"]),
+    ("mouse", &["🐭"]), ("music", &["🎵", "🎶"]), ("night", &["🌙", "🌃"]), ("no", &["❌", "🙅"]),
+    ("nose", &["👃"]), ("ok", &["👌"]), ("party", &["🎉", "🥳", "🎊"]), ("phone", &["📱"]),
+    ("pig", &["🐷", "🐖"]), ("pizza", &["🍕"]), ("poop", &["💩"]), ("queen", &["👑", "👸"]),
+    ("rain", &["🌧️", "☔"]), ("rich", &["💰", "🤑"]), ("rocket", &["🚀"]), ("run", &["🏃"]),
+    ("sad", &["😢", "😞"]), ("school", &["🏫", "🎒"]), ("scream", &["😱"]), ("sea", &["🌊"]),
+    ("shit", &["💩"]), ("sick", &["🤒", "🤢"]), ("sleep", &["😴", "💤"]), ("smile", &["😊", "😁"]),
+    ("snake", &["🐍"]), ("snow", &["❄️", "☃️"]), ("song", &["🎵", "🎤"]), ("space", &["🌌", "🚀"]),
+    ("star", &["⭐", "🌟"]), ("strong", &["💪"]), ("sun", &["☀️", "🌞"]), ("sweet", &["🍬", "🍭"]),
+    ("taco", &["🌮"]), ("tea", &["🍵"]), ("think", &["🤔", "💭"]), ("time", &["⏰", "⌚"]),
+    ("tired", &["😴", "😩"]), ("tree", &["🌳", "🌲"]), ("ugly", &["👹"]), ("water", &["💧", "🌊"]),
+    ("weird", &["🤨", "🤪"]), ("win", &["🏆", "🥇"]), ("wine", &["🍷"]), ("woman", &["👩"]),
+    ("world", &["🌍", "🌎", "🌏"]), ("wow", &["😮", "🤯"]), ("yes", &["✅", "👍"]), ("zombie", &["🧟"]),
+];
+
+/// The most emojis to add after a single word.
+const MAX_PER_WORD: usize = 3;
+
+#[instrument]
+pub fn emojipasta(
+    input: String,
+    seed: Option<u64>,
+    extra: Vec<(String, String)>,
+) -> Result<Response, CommandError> {
+    let mut rng = rng(seed);
+
+    let mut keywords = KEYWORDS
+        .iter()
+        .map(|&(keyword, emojis)| (keyword.to_string(), emojis.to_vec()))
+        .collect::<HashMap<_, _>>();
+
+    for (keyword, emoji) in &extra {
+        keywords
+            .entry(keyword.clone())
+            .or_default()
+            .push(emoji.as_str());
+    }
+
+    let output = pastafy(&input, &keywords, &mut rng);
+    let response = Response::Emojipasta {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+#[instrument]
+pub fn add_keyword(keyword: String, emoji: String) -> Result<Response, CommandError> {
+    let keyword = validate_keyword(keyword)?;
+
+    let emoji = react::parse_emoji(&emoji).ok_or_else(|| {
+        warn!("invalid emoji for keyword");
+
+        CommandError::InvalidArgument {
+            argument: "emoji".to_string(),
+            value: emoji.clone(),
+        }
+    })?;
+
+    let response = Response::EmojipastaAdd { keyword, emoji };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+#[instrument]
+pub fn remove_keyword(keyword: String) -> Result<Response, CommandError> {
+    let response = Response::EmojipastaRemove {
+        keyword: validate_keyword(keyword)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+#[instrument]
+pub fn list_keywords(mut keywords: Vec<(String, String)>) -> Response {
+    keywords.sort();
+
+    let response = Response::EmojipastaKeywords { keywords };
+
+    debug!(?response);
+
+    response
+}
+
+/// Check that a keyword is a single word made of letters, and convert it to
+/// lowercase.
+fn validate_keyword(keyword: String) -> Result<String, CommandError> {
+    if keyword.is_empty() || !keyword.chars().all(char::is_alphabetic) {
+        warn!("invalid keyword");
+
+        return Err(CommandError::InvalidArgument {
+            argument: "keyword".to_string(),
+            value: keyword,
+        });
+    }
+
+    Ok(keyword.to_lowercase())
+}
+
+/// Add between one and [`MAX_PER_WORD`] emojis after every word that matches a
+/// keyword, leaving Discord markup alone.
+fn pastafy<R: Rng>(input: &str, keywords: &HashMap<String, Vec<&str>>, rng: &mut R) -> String {
+    markup::segments(input)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text
+                .split_word_bounds()
+                .map(|word| {
+                    let emojis = match lookup(keywords, word) {
+                        Some(emojis) => emojis,
+                        None => return word.to_string(),
+                    };
+
+                    let count = rng.gen_range(1..=MAX_PER_WORD);
+                    let chosen = (0..count)
+                        .filter_map(|_| emojis.choose(rng).copied())
+                        .collect::<String>();
+
+                    format!("{} {}", word, chosen)
+                })
+                .collect(),
+            Segment::Verbatim(verbatim) => verbatim.to_string(),
+        })
+        .collect()
+}
+
+/// Find the emojis for a word, trying the word itself first and then removing
+/// possessive and plural endings from it.
+fn lookup<'a>(keywords: &'a HashMap<String, Vec<&str>>, word: &str) -> Option<&'a [&'a str]> {
+    let word = word.to_lowercase();
+    let word = word
+        .strip_suffix("'s")
+        .or_else(|| word.strip_suffix("’s"))
+        .unwrap_or(&word);
+
+    let mut stems = vec![word.to_string()];
+
+    if let Some(stem) = word.strip_suffix("ies") {
+        stems.push(format!("{}y", stem));
+    }
+
+    if let Some(stem) = word.strip_suffix("es") {
+        stems.push(stem.to_string());
+    }
+
+    if let Some(stem) = word.strip_suffix('s') {
+        stems.push(stem.to_string());
+    }
+
+    stems
+        .iter()
+        .find_map(|stem| keywords.get(stem))
+        .map(|emojis| emojis.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(input: &str, seed: u64, extra: &[(&str, &str)]) -> String {
+        let extra = extra
+            .iter()
+            .map(|&(keyword, emoji)| (keyword.to_string(), emoji.to_string()))
+            .collect();
+
+        match emojipasta(input.to_string(), Some(seed), extra) {
+            Ok(Response::Emojipasta { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn emojis_are_added_after_keywords() {
+        for seed in 0..10 {
+            let output = output("fox and pizza", seed, &[("fox", "🦊")]);
+            let (fox, rest) = output.split_once(" and ").unwrap();
+
+            assert!(fox.starts_with("fox 🦊"));
+            assert!((1..=MAX_PER_WORD).contains(&fox.matches('🦊').count()));
+            assert!(rest.starts_with("pizza 🍕"));
+        }
+    }
+
+    #[test]
+    fn plurals_and_possessives_match_keywords() {
+        let keywords = [
+            ("puppy".to_string(), vec!["🐶"]),
+            ("box".to_string(), vec!["📦"]),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+        for word in ["puppy", "Puppies", "puppy's", "boxes", "box’s"] {
+            assert!(lookup(&keywords, word).is_some(), "{:?}", word);
+        }
+
+        assert!(lookup(&keywords, "boxing").is_none());
+    }
+
+    #[test]
+    fn same_seed_gives_same_output() {
+        let input = "I love my cat and my dog";
+
+        assert_eq!(output(input, 7, &[]), output(input, 7, &[]));
+    }
+
+    #[test]
+    fn markup_is_left_alone() {
+        assert_eq!(output("`pizza` <@123>", 1, &[]), "`pizza` <@123>");
+    }
+
+    #[test]
+    fn keywords_need_a_single_emoji() {
+        assert!(add_keyword("Fox".to_string(), "🦊".to_string()).is_ok());
+        assert!(add_keyword("fox".to_string(), "<:fox:123>".to_string()).is_ok());
+
+        for emoji in ["", "fox", "🦊🦊"] {
+            assert!(matches!(
+                add_keyword("fox".to_string(), emoji.to_string()),
+                Err(CommandError::InvalidArgument { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn keywords_are_single_words() {
+        match add_keyword("Fox".to_string(), "🦊".to_string()) {
+            Ok(Response::EmojipastaAdd { keyword, .. }) => assert_eq!(keyword, "fox"),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        for keyword in ["", "red fox", "fox2"] {
+            assert!(matches!(
+                remove_keyword(keyword.to_string()),
+                Err(CommandError::InvalidArgument { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Encode text as base64, hex, binary or URL-encoding, and decode it again.
+
+use std::{fmt, str::FromStr};
+
+use base64::{
+    alphabet,
+    engine::{
+        general_purpose::{GeneralPurpose, GeneralPurposeConfig},
+        DecodePaddingMode,
+    },
+    Engine,
+};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use super::{check_length, CommandError, Response};
+
+/// A base64 engine that decodes input whether or not it has padding.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Characters to percent-encode when URL-encoding, which is everything except
+/// the unreserved characters from RFC 3986.
+const URL_ENCODE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// A way of encoding text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Base64, using the standard alphabet with padding. Decoding also accepts
+    /// the URL-safe alphabet and missing padding.
+    Base64,
+    /// Two hexadecimal digits per byte.
+    Hex,
+    /// Eight binary digits per byte.
+    Binary,
+    /// Percent-encoding, as used in URLs.
+    Url,
+}
+
+impl FromStr for Encoding {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "base64" | "b64" => Ok(Encoding::Base64),
+            "hex" => Ok(Encoding::Hex),
+            "binary" | "bin" => Ok(Encoding::Binary),
+            "url" => Ok(Encoding::Url),
+            _ => Err(CommandError::InvalidArgument {
+                argument: "encoding".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Encoding::Base64 => write!(f, "base64"),
+            Encoding::Hex => write!(f, "hex"),
+            Encoding::Binary => write!(f, "binary"),
+            Encoding::Url => write!(f, "URL-encoded text"),
+        }
+    }
+}
+
+#[instrument]
+pub fn encode(input: String, encoding: Encoding) -> Result<Response, CommandError> {
+    let bytes = input.as_bytes();
+
+    let output = match encoding {
+        Encoding::Base64 => BASE64.encode(bytes),
+        Encoding::Hex => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+        Encoding::Binary => bytes
+            .iter()
+            .map(|b| format!("{:08b}", b))
+            .collect::<Vec<_>>()
+            .join(" "),
+        Encoding::Url => utf8_percent_encode(&input, URL_ENCODE).to_string(),
+    };
+
+    let response = Response::Encode {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+#[instrument]
+pub fn decode(input: String, encoding: Encoding) -> Result<Response, CommandError> {
+    // Encoded blobs are often wrapped over several lines, so whitespace is
+    // ignored, except in URL-encoded text where it's part of the text.
+    let compact = || input.split_whitespace().collect::<String>();
+
+    let bytes = match encoding {
+        Encoding::Url => Some(percent_decode_str(&input.replace('+', " ")).collect()),
+        // Accept the URL-safe alphabet too.
+        Encoding::Base64 => BASE64
+            .decode(compact().replace('-', "+").replace('_', "/"))
+            .ok(),
+        Encoding::Hex => decode_radix(&compact(), 2, 16),
+        Encoding::Binary => decode_radix(&compact(), 8, 2),
+    };
+
+    let bytes = match bytes {
+        Some(bytes) => bytes,
+        None => {
+            warn!("input is not validly encoded");
+
+            return Err(CommandError::InvalidEncoding { encoding });
+        }
+    };
+
+    // Not everything that gets encoded is text, so anything that isn't valid
+    // UTF-8 is shown as a hex dump instead.
+    let output = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => {
+            debug!("decoded bytes are not valid UTF-8");
+            hex_dump(e.as_bytes())
+        }
+    };
+
+    let response = Response::Decode {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// Decode input made up of digits in the given radix, where every `width`
+/// digits make up a byte. `width` digits in `radix` must fit in a byte.
+fn decode_radix(input: &str, width: usize, radix: u32) -> Option<Vec<u8>> {
+    input
+        .as_bytes()
+        .chunks(width)
+        .map(|digits| {
+            if digits.len() != width {
+                return None;
+            }
+
+            digits.iter().try_fold(0u8, |byte, &digit| {
+                let value = (digit as char).to_digit(radix)?;
+                Some(byte * radix as u8 + value as u8)
+            })
+        })
+        .collect()
+}
+
+/// Format bytes as a hex dump in a code block, with 16 bytes per line and the
+/// printable ASCII characters alongside.
+fn hex_dump(bytes: &[u8]) -> String {
+    let lines = bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex = chunk
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let ascii = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+                // A run of backticks would end the code block early.
+                .replace('`', ".");
+
+            format!("{:08x}  {:<47}  |{}|", i * 16, hex, ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("```\n{}\n```", lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODINGS: &[Encoding] = &[
+        Encoding::Base64,
+        Encoding::Hex,
+        Encoding::Binary,
+        Encoding::Url,
+    ];
+
+    fn encoded(input: &str, encoding: Encoding) -> String {
+        match encode(input.to_string(), encoding) {
+            Ok(Response::Encode { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    fn decoded(input: &str, encoding: Encoding) -> Result<String, CommandError> {
+        decode(input.to_string(), encoding).map(|response| match response {
+            Response::Decode { output } => output,
+            response => panic!("unexpected response {:?}", response),
+        })
+    }
+
+    #[test]
+    fn text_is_encoded() {
+        assert_eq!(encoded("hi!", Encoding::Base64), "aGkh");
+        assert_eq!(encoded("hi!", Encoding::Hex), "686921");
+        assert_eq!(encoded("hi", Encoding::Binary), "01101000 01101001");
+        assert_eq!(encoded("a b/ü~", Encoding::Url), "a%20b%2F%C3%BC~");
+    }
+
+    #[test]
+    fn encodings_round_trip() {
+        for &encoding in ENCODINGS {
+            for input in ["", "Hello, world!", "ünïcödé 👍", "a+b c"] {
+                assert_eq!(
+                    decoded(&encoded(input, encoding), encoding).unwrap(),
+                    input,
+                    "{:?}",
+                    encoding
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decoding_is_lenient() {
+        assert_eq!(decoded("aGVs\nbG8", Encoding::Base64).unwrap(), "hello");
+        assert_eq!(decoded("aGk_", Encoding::Base64).unwrap(), "hi?");
+        assert_eq!(decoded("68 69", Encoding::Hex).unwrap(), "hi");
+        assert_eq!(decoded("a+b", Encoding::Url).unwrap(), "a b");
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        let cases = [
+            ("6g", Encoding::Hex),
+            ("686", Encoding::Hex),
+            ("0110100", Encoding::Binary),
+            ("01101002", Encoding::Binary),
+            ("a!b", Encoding::Base64),
+        ];
+
+        for (input, encoding) in cases {
+            assert!(
+                matches!(
+                    decoded(input, encoding),
+                    Err(CommandError::InvalidEncoding { encoding: e }) if e == encoding
+                ),
+                "{:?} was decoded",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn bytes_that_are_not_utf8_are_hex_dumped() {
+        let output = decoded("ff41", Encoding::Hex).unwrap();
+
+        assert!(output.starts_with("```\n00000000  ff 41 "), "{}", output);
+        assert!(output.ends_with("  |.A|\n```"), "{}", output);
+    }
+}
@@ -0,0 +1,228 @@
+//! Convert text to one of the styles in the Unicode Mathematical Alphanumeric
+//! Symbols block, or to circled or squared letters.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use std::str::FromStr;
+
+use super::{is_emoji_sequence, CommandError, Response};
+
+/// A style of text to convert to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// 𝐁𝐨𝐥𝐝 𝐭𝐞𝐱𝐭.
+    Bold,
+    /// 𝐼𝑡𝑎𝑙𝑖𝑐 𝑡𝑒𝑥𝑡.
+    Italic,
+    /// 𝑩𝒐𝒍𝒅 𝒊𝒕𝒂𝒍𝒊𝒄 𝒕𝒆𝒙𝒕.
+    BoldItalic,
+    /// 𝒮𝒸𝓇𝒾𝓅𝓉 𝓉ℯ𝓍𝓉.
+    Script,
+    /// 𝔉𝔯𝔞𝔨𝔱𝔲𝔯 𝔱𝔢𝔵𝔱.
+    Fraktur,
+    /// 𝔻𝕠𝕦𝕓𝕝𝕖-𝕤𝕥𝕣𝕦𝕔𝕜 𝕥𝕖𝕩𝕥.
+    DoubleStruck,
+    /// 𝙼𝚘𝚗𝚘𝚜𝚙𝚊𝚌𝚎 𝚝𝚎𝚡𝚝.
+    Monospace,
+    /// 𝖲𝖺𝗇𝗌-𝗌𝖾𝗋𝗂𝖿 𝗍𝖾𝗑𝗍.
+    SansSerif,
+    /// Ⓒⓘⓡⓒⓛⓔⓓ ⓣⓔⓧⓣ.
+    Circled,
+    /// 🅂🅀🅄🄰🅁🄴🄳 🅃🄴🅇🅃.
+    Squared,
+}
+
+impl Style {
+    /// The codepoints of the styled equivalents of `A`, `a` and `0`, if this
+    /// style has them. Other letters and digits follow on consecutively.
+    fn starts(self) -> (Option<u32>, Option<u32>, Option<u32>) {
+        match self {
+            Style::Bold => (Some(0x1d400), Some(0x1d41a), Some(0x1d7ce)),
+            Style::Italic => (Some(0x1d434), Some(0x1d44e), None),
+            Style::BoldItalic => (Some(0x1d468), Some(0x1d482), None),
+            Style::Script => (Some(0x1d49c), Some(0x1d4b6), None),
+            Style::Fraktur => (Some(0x1d504), Some(0x1d51e), None),
+            Style::DoubleStruck => (Some(0x1d538), Some(0x1d552), Some(0x1d7d8)),
+            Style::Monospace => (Some(0x1d670), Some(0x1d68a), Some(0x1d7f6)),
+            Style::SansSerif => (Some(0x1d5a0), Some(0x1d5ba), Some(0x1d7e2)),
+            // Circled digits start from 1, with 0 found elsewhere.
+            Style::Circled => (Some(0x24b6), Some(0x24d0), Some(0x245f)),
+            // There are no squared lowercase letters, so use the uppercase ones.
+            Style::Squared => (Some(0x1f130), Some(0x1f130), None),
+        }
+    }
+
+    /// Some styled characters were encoded in the Letterlike Symbols block
+    /// before the rest of their style, and the positions they would have had
+    /// in the Mathematical Alphanumeric Symbols block are left unassigned.
+    /// Return the real character for any of these holes.
+    fn exception(self, c: char) -> Option<char> {
+        let exception = match (self, c) {
+            (Style::Italic, 'h') => 'ℎ',
+            (Style::Script, 'B') => 'ℬ',
+            (Style::Script, 'E') => 'ℰ',
+            (Style::Script, 'F') => 'ℱ',
+            (Style::Script, 'H') => 'ℋ',
+            (Style::Script, 'I') => 'ℐ',
+            (Style::Script, 'L') => 'ℒ',
+            (Style::Script, 'M') => 'ℳ',
+            (Style::Script, 'R') => 'ℛ',
+            (Style::Script, 'e') => 'ℯ',
+            (Style::Script, 'g') => 'ℊ',
+            (Style::Script, 'o') => 'ℴ',
+            (Style::Fraktur, 'C') => 'ℭ',
+            (Style::Fraktur, 'H') => 'ℌ',
+            (Style::Fraktur, 'I') => 'ℑ',
+            (Style::Fraktur, 'R') => 'ℜ',
+            (Style::Fraktur, 'Z') => 'ℨ',
+            (Style::DoubleStruck, 'C') => 'ℂ',
+            (Style::DoubleStruck, 'H') => 'ℍ',
+            (Style::DoubleStruck, 'N') => 'ℕ',
+            (Style::DoubleStruck, 'P') => 'ℙ',
+            (Style::DoubleStruck, 'Q') => 'ℚ',
+            (Style::DoubleStruck, 'R') => 'ℝ',
+            (Style::DoubleStruck, 'Z') => 'ℤ',
+            (Style::Circled, '0') => '⓪',
+            _ => return None,
+        };
+
+        Some(exception)
+    }
+
+    /// Convert a single character to this style, leaving it as-is if the style
+    /// has no equivalent.
+    fn convert(self, c: char) -> char {
+        if let Some(exception) = self.exception(c) {
+            return exception;
+        }
+
+        let (upper, lower, digit) = self.starts();
+
+        let styled = match c {
+            'A'..='Z' => upper.map(|start| start + (c as u32 - 'A' as u32)),
+            'a'..='z' => lower.map(|start| start + (c as u32 - 'a' as u32)),
+            '0'..='9' => digit.map(|start| start + (c as u32 - '0' as u32)),
+            _ => None,
+        };
+
+        styled.and_then(std::char::from_u32).unwrap_or(c)
+    }
+}
+
+impl FromStr for Style {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "bold" => Ok(Style::Bold),
+            "italic" => Ok(Style::Italic),
+            "bold-italic" | "bolditalic" => Ok(Style::BoldItalic),
+            "script" => Ok(Style::Script),
+            "fraktur" => Ok(Style::Fraktur),
+            "double-struck" | "doublestruck" => Ok(Style::DoubleStruck),
+            "monospace" | "mono" => Ok(Style::Monospace),
+            "sans-serif" | "sans" => Ok(Style::SansSerif),
+            "circled" => Ok(Style::Circled),
+            "squared" => Ok(Style::Squared),
+            _ => Err(CommandError::InvalidArgument {
+                argument: "style".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[instrument]
+pub fn fancy(input: String, style: Style) -> Response {
+    let response = Response::Fancy {
+        output: fancify(&input, style),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+fn fancify(input: &str, style: Style) -> String {
+    input
+        .graphemes(true)
+        .map(|grapheme| {
+            if is_emoji_sequence(grapheme) {
+                grapheme.to_string()
+            } else {
+                grapheme.chars().map(|c| style.convert(c)).collect()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use unicode_normalization::UnicodeNormalization;
+
+    use super::*;
+
+    const STYLES: &[Style] = &[
+        Style::Bold,
+        Style::Italic,
+        Style::BoldItalic,
+        Style::Script,
+        Style::Fraktur,
+        Style::DoubleStruck,
+        Style::Monospace,
+        Style::SansSerif,
+        Style::Circled,
+        Style::Squared,
+    ];
+
+    #[test]
+    fn holes_use_letterlike_symbols() {
+        let cases = [
+            (Style::Italic, "h", "ℎ"),
+            (Style::Script, "BEFHILMR", "ℬℰℱℋℐℒℳℛ"),
+            (Style::Script, "ego", "ℯℊℴ"),
+            (Style::Script, "Ag", "𝒜ℊ"),
+            (Style::Fraktur, "CHIRZ", "ℭℌℑℜℨ"),
+            (Style::DoubleStruck, "CHNPQRZ", "ℂℍℕℙℚℝℤ"),
+        ];
+
+        for (style, input, expected) in cases {
+            assert_eq!(fancify(input, style), expected, "{:?}", style);
+        }
+    }
+
+    #[test]
+    fn circled_digits_start_from_zero() {
+        assert_eq!(fancify("0123456789", Style::Circled), "⓪①②③④⑤⑥⑦⑧⑨");
+    }
+
+    #[test]
+    fn squared_digits_are_left_alone() {
+        assert_eq!(fancify("0123456789", Style::Squared), "0123456789");
+    }
+
+    #[test]
+    fn every_styled_character_is_assigned() {
+        // Each styled character is a compatibility variant of the original,
+        // so a hole that was missed (an unassigned codepoint) would come back
+        // unchanged from NFKC instead.
+        for &style in STYLES {
+            for c in ('A'..='Z').chain('a'..='z').chain('0'..='9') {
+                let styled = style.convert(c);
+
+                if styled == c {
+                    continue;
+                }
+
+                let folded = styled.to_string().nfkc().collect::<String>();
+                let expected = if style == Style::Squared {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                };
+
+                assert_eq!(folded, expected.to_string(), "{:?} {:?}", style, c);
+            }
+        }
+    }
+}
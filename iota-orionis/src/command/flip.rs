@@ -0,0 +1,158 @@
+//! Turn text upside down, or mirror it horizontally.
+//!
+//! Both transforms reverse the order of the input and then swap each character
+//! with its turned or mirrored counterpart. The swaps go both ways, so applying
+//! either transform twice gives back the original text.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{is_emoji_sequence, Response};
+
+/// Pairs of characters that look like each other turned upside down.
+#[rustfmt::skip]
+const FLIPPED: &[(char, char)] = &[
+    ('a', 'ɐ'), ('b', 'q'), ('c', 'ɔ'), ('d', 'p'), ('e', 'ǝ'), ('f', 'ɟ'), ('g', 'ƃ'),
+    ('h', 'ɥ'), ('i', 'ᴉ'), ('j', 'ɾ'), ('k', 'ʞ'), ('l', 'ꞁ'), ('m', 'ɯ'), ('n', 'u'),
+    ('r', 'ɹ'), ('t', 'ʇ'), ('v', 'ʌ'), ('w', 'ʍ'), ('y', 'ʎ'),
+    ('A', '∀'), ('B', 'ꓭ'), ('C', 'Ɔ'), ('D', 'ᗡ'), ('E', 'Ǝ'), ('F', 'Ⅎ'), ('G', '⅁'),
+    ('J', 'ſ'), ('K', 'ꓘ'), ('L', '⅂'), ('M', 'W'), ('P', 'Ԁ'), ('Q', 'Ό'), ('R', 'ᴚ'),
+    ('T', '⊥'), ('U', '∩'), ('V', 'Λ'), ('Y', '⅄'),
+    ('1', 'Ɩ'), ('2', 'ᄅ'), ('3', 'Ɛ'), ('4', 'ㄣ'), ('5', 'ϛ'), ('6', '9'), ('7', 'ㄥ'),
+    ('.', '˙'), (',', '\''), ('?', '¿'), ('!', '¡'), ('"', '„'), ('(', ')'), ('[', ']'),
+    ('{', '}'), ('<', '>'), ('_', '‾'), ('&', '⅋'), (';', '؛'),
+];
+
+/// Pairs of characters that look like each other mirrored horizontally.
+#[rustfmt::skip]
+const MIRRORED: &[(char, char)] = &[
+    ('a', 'ɒ'), ('b', 'd'), ('c', 'ɔ'), ('e', 'ɘ'), ('g', 'ǫ'), ('k', 'ʞ'), ('p', 'q'),
+    ('r', 'ɿ'), ('s', 'ƨ'), ('z', 'ƹ'),
+    ('B', 'ᙠ'), ('C', 'Ɔ'), ('D', 'ᗡ'), ('E', 'Ǝ'), ('F', 'ꟻ'), ('J', 'Ⴑ'), ('K', 'ꓘ'),
+    ('L', '⅃'), ('N', 'И'), ('P', 'ꟼ'), ('R', 'Я'), ('S', 'Ƨ'), ('Z', 'Ƹ'),
+    ('3', 'Ɛ'),
+    ('?', '⸮'), ('(', ')'), ('[', ']'), ('{', '}'), ('<', '>'), ('/', '\\'),
+];
+
+#[instrument]
+pub fn flip(input: String) -> Response {
+    let response = Response::Flip {
+        output: flip_text(&input),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+#[instrument]
+pub fn mirror(input: String) -> Response {
+    let response = Response::Mirror {
+        output: mirror_text(&input),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+/// Turn text upside down, by reversing it and replacing each character with an
+/// upside-down equivalent, where one exists.
+///
+/// This is its own inverse, so flipping text that has already been flipped
+/// turns it the right way up again.
+pub fn flip_text(input: &str) -> String {
+    reverse_and_swap(input, FLIPPED)
+}
+
+/// Mirror text horizontally, by reversing it and replacing each character with
+/// a mirrored equivalent, where one exists.
+///
+/// This is its own inverse, so mirroring text that has already been mirrored
+/// gives back the original text.
+pub fn mirror_text(input: &str) -> String {
+    reverse_and_swap(input, MIRRORED)
+}
+
+/// Determine whether a character is a non-ASCII character produced by
+/// [`flip_text()`] or [`mirror_text()`].
+pub(super) fn is_swapped(c: char) -> bool {
+    !c.is_ascii()
+        && FLIPPED
+            .iter()
+            .chain(MIRRORED)
+            .any(|&(_, swapped)| swapped == c)
+}
+
+/// Replace the characters produced by [`flip_text()`] or [`mirror_text()`] in
+/// a word with the ASCII characters they came from, if the whole word could
+/// have come from one of them. The order of the word is left unchanged.
+///
+/// A word could only have come from a transform if it contains at least one
+/// non-ASCII character that the transform produces, and all of its other
+/// letters and digits are ASCII characters that the transform can produce.
+/// Otherwise, `None` is returned, so that ordinary words that happen to contain
+/// characters like `Λ` are left alone.
+pub(super) fn unswap_word(word: &str) -> Option<String> {
+    [FLIPPED, MIRRORED].into_iter().find_map(|table| {
+        let mut swapped = false;
+
+        for c in word.chars().filter(|c| c.is_alphanumeric()) {
+            if c.is_ascii() && swap(table, c).is_ascii() {
+                continue;
+            } else if !c.is_ascii() && table.iter().any(|&(_, second)| second == c) {
+                swapped = true;
+            } else {
+                return None;
+            }
+        }
+
+        if !swapped {
+            return None;
+        }
+
+        let unswapped = word
+            .chars()
+            .map(|c| {
+                table
+                    .iter()
+                    .find(|&&(_, second)| second == c && !second.is_ascii())
+                    .map(|&(ascii, _)| ascii)
+                    .unwrap_or(c)
+            })
+            .collect();
+
+        Some(unswapped)
+    })
+}
+
+/// Reverse the order of the graphemes in the input, and swap any characters
+/// found in the table with their counterparts.
+fn reverse_and_swap(input: &str, table: &[(char, char)]) -> String {
+    input
+        .graphemes(true)
+        .rev()
+        .map(|grapheme| {
+            if is_emoji_sequence(grapheme) {
+                grapheme.to_string()
+            } else {
+                grapheme.chars().map(|c| swap(table, c)).collect()
+            }
+        })
+        .collect()
+}
+
+/// Swap a character with its counterpart in the table, in either direction.
+fn swap(table: &[(char, char)], c: char) -> char {
+    table
+        .iter()
+        .find_map(|&(first, second)| {
+            if c == first {
+                Some(second)
+            } else if c == second {
+                Some(first)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(c)
+}
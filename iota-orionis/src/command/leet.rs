@@ -0,0 +1,300 @@
+//! Convert text to and from leetspeak.
+
+use super::{
+    markup::{self, Segment},
+    CommandError, Response,
+};
+
+/// Level 1: simple digit substitutions.
+const LEVEL_1: &[(char, &str)] = &[
+    ('a', "4"),
+    ('e', "3"),
+    ('l', "1"),
+    ('o', "0"),
+    ('s', "5"),
+    ('t', "7"),
+];
+
+/// Level 2: more substitutions, including symbols.
+const LEVEL_2: &[(char, &str)] = &[
+    ('a', "4"),
+    ('b', "8"),
+    ('e', "3"),
+    ('g', "9"),
+    ('i', "!"),
+    ('l', "1"),
+    ('o', "0"),
+    ('s', "$"),
+    ('t', "7"),
+    ('z', "2"),
+];
+
+/// Level 3: every letter replaced, mostly with multi-character glyphs.
+const LEVEL_3: &[(char, &str)] = &[
+    ('a', "/-\\"),
+    ('b', "|3"),
+    ('c', "("),
+    ('d', "|)"),
+    ('e', "3"),
+    ('f', "|="),
+    ('g', "6"),
+    ('h', "|-|"),
+    ('i', "!"),
+    ('j', "_|"),
+    ('k', "|<"),
+    ('l', "|_"),
+    ('m', "|\\/|"),
+    ('n', "|\\|"),
+    ('o', "()"),
+    ('p', "|*"),
+    ('q', "0_"),
+    ('r', "|2"),
+    ('s', "5"),
+    ('t', "7"),
+    ('u', "|_|"),
+    ('v', "\\/"),
+    ('w', "\\/\\/"),
+    ('x', "><"),
+    ('y', "`/"),
+    ('z', "2"),
+];
+
+/// Common substitutions that aren't produced at any level, but should still be
+/// recognised when decoding.
+const EXTRA: &[(char, &str)] = &[('a', "@"), ('t', "+"), ('e', "€")];
+
+#[instrument]
+pub fn leet(input: String, level: u8) -> Result<Response, CommandError> {
+    let table = match level {
+        1 => LEVEL_1,
+        2 => LEVEL_2,
+        3 => LEVEL_3,
+        _ => {
+            warn!("invalid leet level");
+
+            return Err(CommandError::InvalidArgument {
+                argument: "level".to_string(),
+                value: level.to_string(),
+            });
+        }
+    };
+
+    let response = Response::Leet {
+        output: leetify(&input, table),
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+#[instrument]
+pub fn unleet(input: String) -> Response {
+    let response = Response::Unleet {
+        output: unleet_text(&input),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+/// Replace letters with their leetspeak equivalents from the table, leaving
+/// Discord markup alone and escaping any glyphs that contain formatting
+/// characters.
+fn leetify(input: &str, table: &[(char, &str)]) -> String {
+    markup::segments(input)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text
+                .chars()
+                .map(|c| {
+                    table
+                        .iter()
+                        .find(|&&(letter, _)| letter == c.to_ascii_lowercase())
+                        .map(|&(_, glyph)| markup::escape(glyph))
+                        .unwrap_or_else(|| c.to_string())
+                })
+                .collect(),
+            Segment::Verbatim(verbatim) => verbatim.to_string(),
+        })
+        .collect()
+}
+
+/// Decode leetspeak from any level back into plain letters, leaving Discord
+/// markup alone.
+///
+/// Glyphs that are a single character, like `4` or `!`, are only decoded inside
+/// words that also contain letters, longer glyphs or glyphs that aren't digits,
+/// ignoring any quotes or punctuation around the word. This keeps ordinary
+/// numbers and punctuation intact, so `Hello! I paid $25.` is left alone while
+/// `8!9` becomes `big`, but it also means that words written entirely in
+/// digits, like `1337`, aren't decoded.
+pub fn unleet_text(input: &str) -> String {
+    markup::segments(input)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => decode(&markup::unescape(text)),
+            Segment::Verbatim(verbatim) => verbatim.to_string(),
+        })
+        .collect()
+}
+
+/// Decode a piece of text.
+///
+/// Glyphs run together can often be read in more than one way (`|_|_` could be
+/// `ll` or `u_`), so rather than greedily taking the first glyph that matches,
+/// this picks the reading that leaves the fewest characters undecoded, and then
+/// the one that uses the fewest glyphs. Where a glyph could stand for more than
+/// one letter, the letter from the lowest level is used.
+fn decode(text: &str) -> String {
+    let in_word = lettered_words(text);
+
+    // For each byte offset, the cost of the best reading of the rest of the
+    // text from that point, as (undecoded characters, glyphs), along with the
+    // letter to output there (if any) and how many bytes it uses.
+    let mut best = vec![((0, 0), None, 0); text.len() + 1];
+
+    for (i, c) in text.char_indices().rev() {
+        let rest = &text[i..];
+        let ((undecoded, glyphs), _, _) = best[i + c.len_utf8()];
+        let mut choice = ((undecoded + 1, glyphs + 1), None, c.len_utf8());
+
+        for &(letter, glyph) in LEVEL_1.iter().chain(LEVEL_2).chain(LEVEL_3).chain(EXTRA) {
+            if glyph.chars().nth(1).is_none() && !in_word[i] {
+                continue;
+            }
+
+            if rest.starts_with(glyph) {
+                let ((undecoded, glyphs), _, _) = best[i + glyph.len()];
+                let cost = (undecoded, glyphs + 1);
+
+                if cost < choice.0 {
+                    choice = (cost, Some(letter), glyph.len());
+                }
+            }
+        }
+
+        best[i] = choice;
+    }
+
+    let mut decoded = String::new();
+    let mut i = 0;
+
+    while i < text.len() {
+        let (_, letter, len) = best[i];
+
+        match letter {
+            Some(letter) => decoded.push(letter),
+            None => decoded.push_str(&text[i..i + len]),
+        }
+
+        i += len;
+    }
+
+    decoded
+}
+
+/// For each byte offset in the text, determine whether it's inside a word that
+/// contains letters, multi-character glyphs or glyphs that aren't digits (as
+/// long as the word isn't a number like `$5`), not counting any quotes around
+/// the word or punctuation that ends a sentence or clause.
+fn lettered_words(text: &str) -> Vec<bool> {
+    let punctuation = |c: char| matches!(c, '.' | ',' | '!' | '?' | ';' | ':' | '"' | '\'');
+
+    let mut in_word = vec![false; text.len() + 1];
+    let mut start = 0;
+
+    for word in text.split_inclusive(char::is_whitespace) {
+        let trimmed = word.trim_end_matches(|c: char| c.is_whitespace() || punctuation(c));
+        // Only quotes are trimmed from the start, as `!` there is a glyph.
+        let core = trimmed.trim_start_matches(['"', '\'']);
+        let core_start = start + (trimmed.len() - core.len());
+
+        let lettered = core.contains(char::is_alphabetic)
+            || LEVEL_3
+                .iter()
+                .any(|&(_, glyph)| glyph.chars().nth(1).is_some() && core.contains(glyph))
+            || (!is_number(core)
+                && LEVEL_1
+                    .iter()
+                    .chain(LEVEL_2)
+                    .chain(LEVEL_3)
+                    .chain(EXTRA)
+                    .any(|&(_, glyph)| {
+                        !glyph.starts_with(|c: char| c.is_ascii_digit()) && core.contains(glyph)
+                    }));
+
+        if lettered {
+            in_word[core_start..core_start + core.len()].fill(true);
+        }
+
+        start += word.len();
+    }
+
+    in_word
+}
+
+/// Determine whether a word looks like a number, such as `$5`, `+44` or `(1)`,
+/// which is left alone even though `$`, `+` and `(` are also glyphs.
+fn is_number(word: &str) -> bool {
+    let digits = word.trim_matches(|c| matches!(c, '$' | '€' | '+' | '(' | ')'));
+
+    !digits.is_empty()
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | ':' | '%'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(input: &str, level: u8) -> String {
+        match leet(input.to_string(), level) {
+            Ok(Response::Leet { output }) => unleet_text(&output),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn level_1_round_trips() {
+        assert_eq!(
+            round_trip("Hello world, this is big", 1),
+            "Hello world, this is big"
+        );
+    }
+
+    #[test]
+    fn level_2_round_trips() {
+        assert_eq!(
+            round_trip("Hello big world is zesty", 2),
+            "Hello big world is zesty"
+        );
+    }
+
+    #[test]
+    fn level_3_round_trips() {
+        assert_eq!(
+            round_trip("hello big world is zesty, meet me at noon", 3),
+            "hello big world is zesty, meet me at noon"
+        );
+    }
+
+    #[test]
+    fn numbers_and_punctuation_are_left_alone() {
+        for input in [
+            "Hello! I am 25.",
+            "I paid $5 or +5 for it!",
+            "1337",
+            "(1) ok",
+        ] {
+            assert_eq!(unleet_text(input), input);
+        }
+    }
+
+    #[test]
+    fn markup_is_left_alone() {
+        assert_eq!(unleet_text("h3y `h3y` <@123>"), "hey `h3y` <@123>");
+    }
+}
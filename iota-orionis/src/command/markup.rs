@@ -0,0 +1,98 @@
+//! Find the parts of a message that text transforms should leave alone.
+//!
+//! Code blocks, inline code, anything in angle brackets (mentions, channel
+//! links, custom emojis and timestamps) and URLs all stop working if their
+//! characters are changed, so transforms that want to preserve them can split
+//! their input with [`segments()`] and only change the [`Segment::Text`]
+//! parts.
+
+/// A piece of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Segment<'a> {
+    /// Ordinary text, which can be transformed.
+    Text(&'a str),
+    /// Markup that should be kept exactly as it is.
+    Verbatim(&'a str),
+}
+
+/// Split the input into ordinary text and markup that should be kept as-is.
+pub(super) fn segments(input: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while let Some(c) = input[i..].chars().next() {
+        // URLs only count if they start a word, so that text like "xhttp://"
+        // isn't treated as a link.
+        let word_start = input[..i].ends_with(char::is_whitespace) || i == 0;
+
+        match verbatim_len(&input[i..], word_start) {
+            Some(len) => {
+                if text_start < i {
+                    segments.push(Segment::Text(&input[text_start..i]));
+                }
+
+                segments.push(Segment::Verbatim(&input[i..i + len]));
+                i += len;
+                text_start = i;
+            }
+            None => i += c.len_utf8(),
+        }
+    }
+
+    if text_start < input.len() {
+        segments.push(Segment::Text(&input[text_start..]));
+    }
+
+    segments
+}
+
+/// If the input starts with some markup, return the length of that markup.
+fn verbatim_len(input: &str, word_start: bool) -> Option<usize> {
+    if let Some(rest) = input.strip_prefix("```") {
+        rest.find("```").map(|end| end + 6)
+    } else if let Some(rest) = input.strip_prefix('`') {
+        rest.find('`').map(|end| end + 2)
+    } else if let Some(rest) = input.strip_prefix('<') {
+        // Mentions and friends never contain whitespace, which stops a stray
+        // `<` from swallowing the rest of the message.
+        rest.find('>')
+            .filter(|&end| !rest[..end].contains(char::is_whitespace))
+            .map(|end| end + 2)
+    } else if word_start && (input.starts_with("http://") || input.starts_with("https://")) {
+        Some(input.find(char::is_whitespace).unwrap_or(input.len()))
+    } else {
+        None
+    }
+}
+
+/// Escape any characters that Discord would interpret as formatting, so that
+/// they appear literally in the message.
+pub(super) fn escape(input: &str) -> String {
+    input.chars().fold(String::new(), |mut escaped, c| {
+        if matches!(c, '\\' | '*' | '_' | '~' | '|' | '`') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+        escaped
+    })
+}
+
+/// Undo [`escape()`], removing the backslashes before escaped characters.
+pub(super) fn unescape(input: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '\\' && matches!(next, '\\' | '*' | '_' | '~' | '|' | '`') => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
@@ -0,0 +1,216 @@
+//! Convert text to and from International Morse Code.
+//!
+//! Letters are separated by spaces and words by ` / `. Prosigns are written in
+//! angle brackets, like `<SK>`, and are sent as a single run of signals.
+
+use super::{check_length, normalize::fold_text, CommandError, Response};
+
+/// Codes for letters, digits and punctuation.
+#[rustfmt::skip]
+const CHARACTERS: &[(char, &str)] = &[
+    ('A', ".-"), ('B', "-..."), ('C', "-.-."), ('D', "-.."), ('E', "."), ('F', "..-."),
+    ('G', "--."), ('H', "...."), ('I', ".."), ('J', ".---"), ('K', "-.-"), ('L', ".-.."),
+    ('M', "--"), ('N', "-."), ('O', "---"), ('P', ".--."), ('Q', "--.-"), ('R', ".-."),
+    ('S', "..."), ('T', "-"), ('U', "..-"), ('V', "...-"), ('W', ".--"), ('X', "-..-"),
+    ('Y', "-.--"), ('Z', "--.."),
+    ('0', "-----"), ('1', ".----"), ('2', "..---"), ('3', "...--"), ('4', "....-"),
+    ('5', "....."), ('6', "-...."), ('7', "--..."), ('8', "---.."), ('9', "----."),
+    ('.', ".-.-.-"), (',', "--..--"), ('?', "..--.."), ('\'', ".----."), ('!', "-.-.--"),
+    ('/', "-..-."), ('(', "-.--."), (')', "-.--.-"), ('&', ".-..."), (':', "---..."),
+    (';', "-.-.-."), ('=', "-...-"), ('+', ".-.-."), ('-', "-....-"), ('_', "..--.-"),
+    ('"', ".-..-."), ('$', "...-..-"), ('@', ".--.-."),
+];
+
+/// Codes for prosigns. Some of these share a code with a punctuation mark (`<AR>`
+/// is the same as `+`, for example), in which case decoding gives the
+/// punctuation mark.
+#[rustfmt::skip]
+const PROSIGNS: &[(&str, &str)] = &[
+    ("AR", ".-.-."), ("AS", ".-..."), ("BT", "-...-"), ("CT", "-.-.-"), ("HH", "........"),
+    ("KN", "-.--."), ("SK", "...-.-"), ("SN", "...-."), ("SOS", "...---..."),
+];
+
+#[instrument]
+pub fn morse(input: String) -> Result<Response, CommandError> {
+    let mut output = encode(&input);
+
+    // Discord shows a message starting with "- " as a list, so the first dash
+    // has to be escaped.
+    if output.starts_with('-') {
+        output.insert(0, '\\');
+    }
+
+    let response = Response::Morse {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+#[instrument]
+pub fn unmorse(input: String) -> Result<Response, CommandError> {
+    let response = Response::Unmorse {
+        output: decode(&input)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// Encode text as Morse code.
+///
+/// The input is normalized first, so that accented and decorated letters are
+/// encoded as the plain letters they're based on. Any other characters that
+/// have no Morse code are left out.
+fn encode(input: &str) -> String {
+    let normalized = fold_text(input).to_uppercase();
+
+    normalized
+        .split_whitespace()
+        .map(|word| {
+            let mut codes = Vec::new();
+            let mut rest = word;
+
+            while let Some(c) = rest.chars().next() {
+                if let Some((code, len)) = prosign(rest) {
+                    codes.push(code);
+                    rest = &rest[len..];
+                    continue;
+                }
+
+                if let Some(&(_, code)) = CHARACTERS.iter().find(|&&(plain, _)| plain == c) {
+                    codes.push(code);
+                }
+
+                rest = &rest[c.len_utf8()..];
+            }
+
+            codes.join(" ")
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// If the input starts with a prosign in angle brackets, return its code and
+/// the length of the prosign in the input.
+fn prosign(input: &str) -> Option<(&'static str, usize)> {
+    let rest = input.strip_prefix('<')?;
+    let end = rest.find('>')?;
+
+    PROSIGNS
+        .iter()
+        .find(|&&(name, _)| name == &rest[..end])
+        .map(|&(_, code)| (code, end + 2))
+}
+
+/// Decode Morse code into text, or list the symbols that couldn't be decoded.
+///
+/// Words can be separated by `/` or `|`. Phones tend to replace dots and dashes
+/// with lookalike characters, so those are accepted as well.
+fn decode(input: &str) -> Result<String, CommandError> {
+    let input = input
+        .trim_start_matches('\\')
+        .replace(['—', '–', '−', '_'], "-")
+        .replace(['·', '•', '∙'], ".");
+
+    let mut invalid: Vec<String> = Vec::new();
+
+    let words = input
+        .split(['/', '|'])
+        .map(|word| {
+            word.split_whitespace()
+                .map(|symbol| match lookup(symbol) {
+                    Some(decoded) => decoded,
+                    None => {
+                        if !invalid.iter().any(|seen| seen == symbol) {
+                            invalid.push(symbol.to_string());
+                        }
+
+                        String::new()
+                    }
+                })
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+
+    if !invalid.is_empty() {
+        warn!(?invalid, "input contains invalid Morse code");
+
+        return Err(CommandError::InvalidMorse { symbols: invalid });
+    }
+
+    Ok(words.join(" "))
+}
+
+/// Decode a single symbol, preferring characters over prosigns.
+fn lookup(symbol: &str) -> Option<String> {
+    CHARACTERS
+        .iter()
+        .find(|&&(_, code)| code == symbol)
+        .map(|&(c, _)| c.to_string())
+        .or_else(|| {
+            PROSIGNS
+                .iter()
+                .find(|&&(_, code)| code == symbol)
+                .map(|&(name, _)| format!("<{}>", name))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(input: &str) -> String {
+        match morse(input.to_string()) {
+            Ok(Response::Morse { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    fn decoded(input: &str) -> Result<String, CommandError> {
+        unmorse(input.to_string()).map(|response| match response {
+            Response::Unmorse { output } => output,
+            response => panic!("unexpected response {:?}", response),
+        })
+    }
+
+    #[test]
+    fn text_is_encoded() {
+        assert_eq!(encoded("sos"), "... --- ...");
+        assert_eq!(encoded("hi  there"), ".... .. / - .... . .-. .");
+        assert_eq!(encoded("café"), encoded("cafe"));
+    }
+
+    #[test]
+    fn leading_dash_is_escaped() {
+        assert_eq!(encoded("to"), "\\- ---");
+    }
+
+    #[test]
+    fn morse_round_trips() {
+        for input in ["HELLO WORLD", "SOS 123", "WAIT <SK>"] {
+            assert_eq!(decoded(&encoded(input)).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn lookalike_characters_are_accepted() {
+        assert_eq!(decoded("·– —···").unwrap(), "AB");
+        assert_eq!(decoded(".- | -...").unwrap(), "A B");
+    }
+
+    #[test]
+    fn invalid_symbols_are_listed_once() {
+        match decoded(".- -------- .- ........- --------") {
+            Err(CommandError::InvalidMorse { symbols }) => {
+                assert_eq!(symbols, vec!["--------", "........-"]);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
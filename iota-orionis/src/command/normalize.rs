@@ -0,0 +1,120 @@
+//! Convert decorated text back into plain text.
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{flip, overlay, small, zalgo, Response};
+
+/// Styled letters from the Letterlike Symbols block, which fill the holes in
+/// the Mathematical Alphanumeric Symbols block.
+const LETTERLIKE: &[char] = &[
+    'ℂ', 'ℊ', 'ℋ', 'ℌ', 'ℍ', 'ℎ', 'ℐ', 'ℑ', 'ℒ', 'ℕ', 'ℙ', 'ℚ', 'ℛ', 'ℜ', 'ℝ', 'ℤ', 'ℨ', 'ℬ', 'ℭ',
+    'ℯ', 'ℰ', 'ℱ', 'ℳ', 'ℴ',
+];
+
+/// The number of `zalgo` combining characters a character needs before they're
+/// treated as decoration. Real text never stacks this many accents on one
+/// letter, but `zalgo` always adds at least this many unless it's using
+/// [`Intensity::Mini`][super::Intensity::Mini].
+const MIN_ZALGO_COMBINERS: usize = 3;
+
+#[instrument]
+pub fn normalize(input: String) -> Response {
+    let response = Response::Normalize {
+        output: normalize_text(&input),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+/// Undo the decorations added by the text transforms, converting the input back
+/// to the plain text it came from.
+///
+/// Only characters that the transforms produce are changed, so that ordinary
+/// text in any language survives:
+///
+/// - Fullwidth, mathematical alphanumeric, circled and squared characters are
+///   replaced with their plain equivalents, and regional indicators with the
+///   uppercase letters they represent.
+/// - Small capital, superscript and subscript characters are replaced with the
+///   characters they came from, so `m²` becomes `m2`.
+/// - Combining characters added by `zalgo` are removed from any character with
+///   at least [`MIN_ZALGO_COMBINERS`] of them, and those added by `overlay` are
+///   always removed. Other combining characters, such as accents, are kept.
+/// - Upside-down and mirrored characters are replaced with the characters they
+///   came from, although the order of reversed text is left unchanged. This
+///   only happens to words that could entirely have come from `flip` or
+///   `mirror`, and only if the text contains no other non-ASCII letters, so
+///   that Greek and Cyrillic text isn't mistaken for them.
+///
+/// The output is in Unicode normalization form C, so decomposed accents are
+/// combined with their letters.
+pub fn normalize_text(input: &str) -> String {
+    let undecorated = input
+        .graphemes(true)
+        .flat_map(|grapheme| {
+            // Decompose the grapheme first, so that accents that can be
+            // combined with their letters are counted, and don't survive when
+            // the rest are removed.
+            let decomposed = grapheme.nfd().collect::<Vec<_>>();
+            let combiners = decomposed
+                .iter()
+                .filter(|&&c| zalgo::is_combiner(c))
+                .count();
+
+            decomposed.into_iter().filter(move |&c| {
+                let zalgo = combiners >= MIN_ZALGO_COMBINERS && zalgo::is_combiner(c);
+                !zalgo && !overlay::is_combiner(c)
+            })
+        })
+        .nfc()
+        .flat_map(undecorate)
+        .map(small::unsmall)
+        .collect::<String>();
+
+    // Letters that don't come from any transform mean that the text is in
+    // another language, so any flipped-looking characters are probably real.
+    let foreign = undecorated
+        .chars()
+        .any(|c| c.is_alphabetic() && !c.is_ascii() && !flip::is_swapped(c));
+
+    if foreign {
+        return undecorated;
+    }
+
+    undecorated
+        .split_inclusive(char::is_whitespace)
+        .map(|word| flip::unswap_word(word).unwrap_or_else(|| word.to_string()))
+        .collect()
+}
+
+/// Like [`normalize_text()`], but also remove all accents and replace any
+/// compatibility characters with their plain equivalents, as close to plain
+/// ASCII as possible.
+///
+/// This loses information, so it's only meant for transforms that can only
+/// render ASCII, such as Morse code.
+pub(super) fn fold_text(input: &str) -> String {
+    normalize_text(input)
+        .nfkd()
+        .filter(|&c| !is_combining_mark(c))
+        .collect()
+}
+
+/// Replace a decorated character with the plain characters it represents, or
+/// return it as-is if it isn't decorated.
+fn undecorate(c: char) -> Vec<char> {
+    match c as u32 {
+        // Regional indicators have no compatibility decomposition.
+        0x1f1e6..=0x1f1ff => vec![std::char::from_u32(c as u32 - 0x1f1a5).unwrap_or(c)],
+        // Fullwidth, circled, mathematical alphanumeric and squared
+        // characters.
+        0x3000 | 0xff01..=0xff5e | 0x2460..=0x24ff | 0x1d400..=0x1d7ff | 0x1f100..=0x1f1ff => {
+            c.to_string().nfkd().collect()
+        }
+        _ if LETTERLIKE.contains(&c) => c.to_string().nfkd().collect(),
+        _ => vec![c],
+    }
+}
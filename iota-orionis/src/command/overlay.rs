@@ -0,0 +1,80 @@
+//! Style text with combining characters that draw a line over, under or
+//! through every character, for places where Discord's markdown doesn't work.
+
+use std::str::FromStr;
+
+use super::{check_length, zalgo, CommandError, Response};
+
+/// A line to draw across text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+    /// A horizontal line through the middle, using U+0336 COMBINING LONG STROKE
+    /// OVERLAY.
+    Strike,
+    /// A line underneath, using U+0332 COMBINING LOW LINE.
+    Underline,
+    /// A line above, using U+0305 COMBINING OVERLINE.
+    Overline,
+    /// A diagonal line through the middle, using U+0338 COMBINING LONG SOLIDUS
+    /// OVERLAY.
+    Slash,
+}
+
+impl Overlay {
+    /// All the overlays.
+    pub const ALL: [Overlay; 4] = [
+        Overlay::Strike,
+        Overlay::Underline,
+        Overlay::Overline,
+        Overlay::Slash,
+    ];
+
+    /// The combining character that draws this line.
+    fn combiner(self) -> char {
+        match self {
+            Overlay::Strike => '\u{0336}',
+            Overlay::Underline => '\u{0332}',
+            Overlay::Overline => '\u{0305}',
+            Overlay::Slash => '\u{0338}',
+        }
+    }
+}
+
+impl FromStr for Overlay {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "strike" | "strikethrough" => Ok(Overlay::Strike),
+            "underline" => Ok(Overlay::Underline),
+            "overline" => Ok(Overlay::Overline),
+            "slash" => Ok(Overlay::Slash),
+            _ => Err(CommandError::InvalidArgument {
+                argument: "overlay".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Determine whether a character is one of the combining characters that
+/// [`overlay()`] adds.
+pub(super) fn is_combiner(c: char) -> bool {
+    Overlay::ALL.iter().any(|overlay| overlay.combiner() == c)
+}
+
+#[instrument]
+pub fn overlay(input: String, overlay: Overlay) -> Result<Response, CommandError> {
+    let combiner = overlay.combiner();
+    let output = zalgo::append_combiners(&input, |decorated| decorated.push(combiner));
+
+    // Adding a character after every grapheme can double the length of the
+    // input, which might take it over the limit.
+    let response = Response::Overlay {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
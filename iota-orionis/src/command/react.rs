@@ -1,53 +1,148 @@
-//! The react command converts an ASCII-alphanumeric string into a series of
-//! reaction emojis, which it adds to a target message.
+//! The react command converts a string of ASCII-alphanumeric characters and
+//! emojis into a series of reaction emojis, which it adds to a target message.
 
-use std::collections::HashMap;
+use std::collections::HashSet;
 
-use super::{CommandError, Response};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{CommandError, Reaction, Response};
 
 #[instrument]
 pub fn react(input: String) -> Result<Response, CommandError> {
-    // Ignore spaces by removing them before checking if the input is valid.
-    let input = input.replace(" ", "");
+    // Spaces are ignored, so remove them from the input before reporting any
+    // errors.
+    let original = input.split_whitespace().collect::<String>();
 
     // An input string is only valid if it is entirely composed of alphanumeric
-    // characters, and if each one only appears once.
-    let non_alphanum = input.contains(|c: char| !c.is_alphanumeric());
-    let valid = !non_alphanum
-        && input
-            .chars()
-            .fold(HashMap::new(), |mut acc, next| {
-                *acc.entry(next.to_ascii_uppercase()).or_insert(0) += 1;
-                acc
-            })
-            .values()
-            .all(|&v| v == 1);
+    // characters and emojis, and if each resulting reaction only appears once.
+    let reactions = match parse(&input) {
+        Some(reactions) => reactions,
+        None => {
+            warn!("string contains non-alphanumeric characters");
+
+            return Err(CommandError::NonAlphanumeric { original });
+        }
+    };
+
+    let mut seen = HashSet::new();
+    let valid = reactions.iter().all(|reaction| seen.insert(reaction));
 
     if valid {
-        let response = Response::React {
-            reactions: to_reactions(&input),
-        };
+        let response = Response::React { reactions };
 
         Ok(response)
-    } else if non_alphanum {
-        warn!("string contains non-alphanumeric characters");
-
-        Err(CommandError::NonAlphanumeric { original: input })
     } else {
         warn!("string contains repeated characters");
 
-        Err(CommandError::Repetition { original: input })
+        Err(CommandError::Repetition { original })
     }
 }
 
+/// Parse an input string into the sequence of reactions it represents, in
+/// order. ASCII alphanumeric characters are converted with [`to_reactions()`],
+/// while emojis (including custom emojis in the form `<:name:id>`) are used
+/// as-is. Other letters, like `é`, have no reaction and aren't allowed.
+///
+/// If the input contains anything else, `None` is returned.
+fn parse(input: &str) -> Option<Vec<Reaction>> {
+    let mut reactions = Vec::new();
+    let mut rest = input;
+
+    while let Some(grapheme) = rest.graphemes(true).next() {
+        if let Some((custom, tail)) = parse_custom(rest) {
+            reactions.push(custom);
+            rest = tail;
+            continue;
+        }
+
+        rest = &rest[grapheme.len()..];
+
+        if grapheme.chars().all(char::is_whitespace) {
+            continue;
+        } else if grapheme.chars().all(|c| c.is_ascii_alphanumeric()) {
+            reactions.extend(to_reactions(grapheme).into_iter().map(Reaction::Unicode));
+        } else if is_emoji(grapheme) {
+            reactions.push(Reaction::Unicode(grapheme.to_string()));
+        } else {
+            return None;
+        }
+    }
+
+    Some(reactions)
+}
+
+/// Attempt to parse a custom emoji, in the form `<:name:id>` or `<a:name:id>`,
+/// from the start of the input. If successful, the emoji is returned along with
+/// the remainder of the input.
+pub(super) fn parse_custom(input: &str) -> Option<(Reaction, &str)> {
+    let (animated, tail) = match input.strip_prefix("<a:") {
+        Some(tail) => (true, tail),
+        None => (false, input.strip_prefix("<:")?),
+    };
+
+    let (inner, tail) = tail.split_once('>')?;
+    let (name, id) = inner.split_once(':')?;
+
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let custom = Reaction::Custom {
+        animated,
+        id: id.parse().ok()?,
+        name: name.to_string(),
+    };
+
+    Some((custom, tail))
+}
+
 const VARIATION_SELECTOR_16: u32 = 0xfe0f;
 const COMBINING_ENCLOSING_KEYCAP: u32 = 0x20e3;
 
+/// Parse input that should be a single emoji, either Unicode or custom.
+pub(super) fn parse_emoji(input: &str) -> Option<Reaction> {
+    match parse_custom(input) {
+        Some((custom, "")) => Some(custom),
+        None if input.graphemes(true).count() == 1 && is_emoji(input) => {
+            Some(Reaction::Unicode(input.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Determine whether a grapheme cluster looks like an emoji.
+///
+/// This only checks whether the grapheme contains a character from one of the
+/// blocks that emojis live in (or an emoji presentation selector), rather than
+/// checking against a full list of emojis. Anything that slips through will be
+/// rejected by Discord when it's used as a reaction.
+pub(super) fn is_emoji(grapheme: &str) -> bool {
+    grapheme.chars().any(|c| {
+        matches!(
+            c as u32,
+            0x00a9
+                | 0x00ae
+                | 0x2122
+                | 0x2190..=0x21ff
+                | 0x2300..=0x23ff
+                | 0x2600..=0x27bf
+                | 0x2b00..=0x2bff
+                | 0x3030
+                | 0x303d
+                | 0x3297
+                | 0x3299
+                | 0x1f000..=0x1faff
+                | VARIATION_SELECTOR_16
+                | COMBINING_ENCLOSING_KEYCAP
+        )
+    })
+}
+
 /// Convert a string to a sequence of emojis representing its characters, using
 /// regional indicators for alphabetic characters and keycap sequences for
 /// numerals. Any non-ascii-alphanumeric characters are simply left as-is in the
 /// output string.
-fn to_reactions(input: &str) -> Vec<String> {
+pub(super) fn to_reactions(input: &str) -> Vec<String> {
     input
         .chars()
         .map(|c| match c {
@@ -70,3 +165,50 @@ fn to_reactions(input: &str) -> Vec<String> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reactions(input: &str) -> Vec<Reaction> {
+        match react(input.to_string()) {
+            Ok(Response::React { reactions }) => reactions,
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn letters_digits_and_emojis_become_reactions() {
+        assert_eq!(
+            reactions("hi 5 👍"),
+            vec![
+                Reaction::Unicode("🇭".to_string()),
+                Reaction::Unicode("🇮".to_string()),
+                Reaction::Unicode("5\u{fe0f}\u{20e3}".to_string()),
+                Reaction::Unicode("👍".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn non_ascii_letters_are_rejected() {
+        for input in ["café", "cafe\u{301}", "ñ", "ß"] {
+            assert!(
+                matches!(
+                    react(input.to_string()),
+                    Err(CommandError::NonAlphanumeric { .. })
+                ),
+                "{:?} was accepted",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn repeated_reactions_are_rejected() {
+        assert!(matches!(
+            react("hello".to_string()),
+            Err(CommandError::Repetition { .. })
+        ));
+    }
+}
@@ -0,0 +1,124 @@
+//! Shuffle the letters in the middle of each word, keeping the first and last
+//! letters in place, which is supposedly still easy to read.
+
+use rand::{seq::SliceRandom, Rng};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{
+    markup::{self, Segment},
+    rng, Response,
+};
+
+/// How many times to reshuffle a word that comes out the same as it went in.
+const MAX_ATTEMPTS: usize = 5;
+
+#[instrument]
+pub fn scramble(input: String, seed: Option<u64>) -> Response {
+    let mut rng = rng(seed);
+
+    let output = markup::segments(&input)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text
+                .split_word_bounds()
+                .map(|word| scramble_word(word, &mut rng))
+                .collect(),
+            Segment::Verbatim(verbatim) => verbatim.to_string(),
+        })
+        .collect();
+
+    let response = Response::Scramble { output };
+
+    debug!(?response);
+
+    response
+}
+
+/// Shuffle the graphemes of a word, apart from the first and last ones.
+///
+/// Words with fewer than four letters can't be scrambled, and neither can
+/// anything that isn't a word, like punctuation or numbers.
+fn scramble_word<R: Rng>(word: &str, rng: &mut R) -> String {
+    let mut graphemes = word.graphemes(true).collect::<Vec<_>>();
+
+    if graphemes.len() < 4 || !word.chars().all(char::is_alphabetic) {
+        return word.to_string();
+    }
+
+    let last = graphemes.len() - 1;
+    let original = graphemes.clone();
+
+    // Shuffling can easily give back the same word, especially when the middle
+    // has lots of repeated letters, so try a few times to get something new.
+    for _ in 0..MAX_ATTEMPTS {
+        graphemes[1..last].shuffle(rng);
+
+        if graphemes != original {
+            break;
+        }
+    }
+
+    graphemes.concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(input: &str, seed: u64) -> String {
+        match scramble(input.to_string(), Some(seed)) {
+            Response::Scramble { output } => output,
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+
+    fn sorted(word: &str) -> Vec<char> {
+        let mut chars = word.chars().collect::<Vec<_>>();
+        chars.sort_unstable();
+        chars
+    }
+
+    #[test]
+    fn same_seed_gives_same_output() {
+        let input = "According to research at Cambridge University";
+
+        for seed in 0..10 {
+            assert_eq!(output(input, seed), output(input, seed));
+        }
+    }
+
+    #[test]
+    fn only_middle_letters_move() {
+        let input = "scrambled letters, however, remain readable";
+
+        for seed in 0..10 {
+            let output = output(input, seed);
+
+            for (original, scrambled) in input.split(' ').zip(output.split(' ')) {
+                assert_eq!(original.chars().next(), scrambled.chars().next());
+                assert_eq!(
+                    original.trim_end_matches(',').chars().last(),
+                    scrambled.trim_end_matches(',').chars().last()
+                );
+                assert_eq!(sorted(original), sorted(scrambled));
+            }
+        }
+    }
+
+    #[test]
+    fn words_with_a_fixed_order_are_left_alone() {
+        for seed in 0..10 {
+            assert_eq!(
+                output("the cat ate 1234 x2y4 `longword`", seed),
+                "the cat ate 1234 x2y4 `longword`"
+            );
+        }
+    }
+
+    #[test]
+    fn long_words_change() {
+        for seed in 0..10 {
+            assert_ne!(output("abcdefgh", seed), "abcdefgh");
+        }
+    }
+}
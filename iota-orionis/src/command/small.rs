@@ -0,0 +1,115 @@
+//! Convert text to small capital, superscript or subscript characters.
+//!
+//! Unicode only has small capital, superscript and subscript forms of some
+//! characters, so any character without an equivalent is left as-is in the
+//! output, in the same way that vaporwave text leaves non-ASCII characters
+//! alone.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{is_emoji_sequence, Response};
+
+/// Small capital forms of lowercase letters. There is no small capital X.
+#[rustfmt::skip]
+const SMALL_CAPS: &[(char, char)] = &[
+    ('a', 'ᴀ'), ('b', 'ʙ'), ('c', 'ᴄ'), ('d', 'ᴅ'), ('e', 'ᴇ'), ('f', 'ꜰ'), ('g', 'ɢ'),
+    ('h', 'ʜ'), ('i', 'ɪ'), ('j', 'ᴊ'), ('k', 'ᴋ'), ('l', 'ʟ'), ('m', 'ᴍ'), ('n', 'ɴ'),
+    ('o', 'ᴏ'), ('p', 'ᴘ'), ('q', 'ꞯ'), ('r', 'ʀ'), ('s', 'ꜱ'), ('t', 'ᴛ'), ('u', 'ᴜ'),
+    ('v', 'ᴠ'), ('w', 'ᴡ'), ('y', 'ʏ'), ('z', 'ᴢ'),
+];
+
+/// Superscript forms of letters, digits and symbols. There are no widely
+/// supported superscript forms of q, C, F, Q, S, X, Y or Z.
+#[rustfmt::skip]
+const SUPERSCRIPT: &[(char, char)] = &[
+    ('a', 'ᵃ'), ('b', 'ᵇ'), ('c', 'ᶜ'), ('d', 'ᵈ'), ('e', 'ᵉ'), ('f', 'ᶠ'), ('g', 'ᵍ'),
+    ('h', 'ʰ'), ('i', 'ⁱ'), ('j', 'ʲ'), ('k', 'ᵏ'), ('l', 'ˡ'), ('m', 'ᵐ'), ('n', 'ⁿ'),
+    ('o', 'ᵒ'), ('p', 'ᵖ'), ('r', 'ʳ'), ('s', 'ˢ'), ('t', 'ᵗ'), ('u', 'ᵘ'), ('v', 'ᵛ'),
+    ('w', 'ʷ'), ('x', 'ˣ'), ('y', 'ʸ'), ('z', 'ᶻ'),
+    ('A', 'ᴬ'), ('B', 'ᴮ'), ('D', 'ᴰ'), ('E', 'ᴱ'), ('G', 'ᴳ'), ('H', 'ᴴ'), ('I', 'ᴵ'),
+    ('J', 'ᴶ'), ('K', 'ᴷ'), ('L', 'ᴸ'), ('M', 'ᴹ'), ('N', 'ᴺ'), ('O', 'ᴼ'), ('P', 'ᴾ'),
+    ('R', 'ᴿ'), ('T', 'ᵀ'), ('U', 'ᵁ'), ('V', 'ⱽ'), ('W', 'ᵂ'),
+    ('0', '⁰'), ('1', '¹'), ('2', '²'), ('3', '³'), ('4', '⁴'), ('5', '⁵'), ('6', '⁶'),
+    ('7', '⁷'), ('8', '⁸'), ('9', '⁹'),
+    ('+', '⁺'), ('-', '⁻'), ('=', '⁼'), ('(', '⁽'), (')', '⁾'),
+];
+
+/// Subscript forms of letters, digits and symbols. Only some lowercase letters
+/// have subscript forms, and no uppercase letters do.
+#[rustfmt::skip]
+const SUBSCRIPT: &[(char, char)] = &[
+    ('a', 'ₐ'), ('e', 'ₑ'), ('h', 'ₕ'), ('i', 'ᵢ'), ('j', 'ⱼ'), ('k', 'ₖ'), ('l', 'ₗ'),
+    ('m', 'ₘ'), ('n', 'ₙ'), ('o', 'ₒ'), ('p', 'ₚ'), ('r', 'ᵣ'), ('s', 'ₛ'), ('t', 'ₜ'),
+    ('u', 'ᵤ'), ('v', 'ᵥ'), ('x', 'ₓ'),
+    ('0', '₀'), ('1', '₁'), ('2', '₂'), ('3', '₃'), ('4', '₄'), ('5', '₅'), ('6', '₆'),
+    ('7', '₇'), ('8', '₈'), ('9', '₉'),
+    ('+', '₊'), ('-', '₋'), ('=', '₌'), ('(', '₍'), (')', '₎'),
+];
+
+#[instrument]
+pub fn smallcaps(input: String) -> Response {
+    let response = Response::SmallCaps {
+        output: replace(&input, SMALL_CAPS),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+#[instrument]
+pub fn superscript(input: String) -> Response {
+    let response = Response::Superscript {
+        output: replace(&input, SUPERSCRIPT),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+#[instrument]
+pub fn subscript(input: String) -> Response {
+    let response = Response::Subscript {
+        output: replace(&input, SUBSCRIPT),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+/// Replace a small capital, superscript or subscript character with the
+/// character it came from.
+pub(super) fn unsmall(c: char) -> char {
+    SMALL_CAPS
+        .iter()
+        .chain(SUPERSCRIPT)
+        .chain(SUBSCRIPT)
+        .find(|&&(_, small)| small == c)
+        .map(|&(plain, _)| plain)
+        .unwrap_or(c)
+}
+
+/// Replace every character in the input that has an equivalent in the table.
+fn replace(input: &str, table: &[(char, char)]) -> String {
+    input
+        .graphemes(true)
+        .map(|grapheme| {
+            if is_emoji_sequence(grapheme) {
+                return grapheme.to_string();
+            }
+
+            grapheme
+                .chars()
+                .map(|c| {
+                    table
+                        .iter()
+                        .find(|&&(plain, _)| plain == c)
+                        .map(|&(_, replaced)| replaced)
+                        .unwrap_or(c)
+                })
+                .collect()
+        })
+        .collect()
+}
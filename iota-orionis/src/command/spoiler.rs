@@ -0,0 +1,183 @@
+//! Hide text behind spoiler tags, one word or character at a time, so that it
+//! has to be revealed piece by piece.
+
+use std::str::FromStr;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{
+    check_length,
+    markup::{self, Segment},
+    CommandError, Response,
+};
+
+/// How much of the text to hide behind each spoiler tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Granularity {
+    /// Hide each word separately.
+    #[default]
+    Word,
+    /// Hide each character separately.
+    Char,
+}
+
+impl FromStr for Granularity {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "word" | "words" => Ok(Granularity::Word),
+            "char" | "chars" | "character" | "characters" => Ok(Granularity::Char),
+            _ => Err(CommandError::InvalidArgument {
+                argument: "granularity".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[instrument]
+pub fn spoiler(input: String, granularity: Granularity) -> Result<Response, CommandError> {
+    let mut output = String::new();
+
+    for segment in markup::segments(&input) {
+        match segment {
+            // Splitting up or escaping code or a mention would break it, so
+            // it's hidden in one piece as it is.
+            Segment::Verbatim(verbatim) => {
+                output.push_str("||");
+                output.push_str(verbatim);
+                output.push_str("||");
+            }
+            Segment::Text(text) => match granularity {
+                Granularity::Word => hide_words(&mut output, text),
+                Granularity::Char => hide_chars(&mut output, text),
+            },
+        }
+    }
+
+    // Every piece gains four characters, so even short inputs can end up over
+    // the limit.
+    let response = Response::Spoiler {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// Hide each run of non-whitespace characters, keeping the whitespace between
+/// them visible.
+fn hide_words(output: &mut String, text: &str) {
+    let mut rest = text;
+
+    while let Some(first) = rest.chars().next() {
+        let space = first.is_whitespace();
+        let end = rest
+            .find(|c: char| c.is_whitespace() != space)
+            .unwrap_or(rest.len());
+
+        if space {
+            output.push_str(&rest[..end]);
+        } else {
+            hide(output, &rest[..end]);
+        }
+
+        rest = &rest[end..];
+    }
+}
+
+/// Hide each grapheme, keeping whitespace visible. An escaped `|` is kept
+/// together as one piece.
+fn hide_chars(output: &mut String, text: &str) {
+    let mut rest = text;
+
+    while let Some(grapheme) = rest.graphemes(true).next() {
+        let piece = if rest.starts_with("\\|") {
+            "\\|"
+        } else {
+            grapheme
+        };
+
+        if piece.chars().all(char::is_whitespace) {
+            output.push_str(piece);
+        } else {
+            hide(output, piece);
+        }
+
+        rest = &rest[piece.len()..];
+    }
+}
+
+/// Wrap a piece of text in spoiler tags, escaping any `|` inside it so that it
+/// can't close the tag early.
+fn hide(output: &mut String, piece: &str) {
+    output.push_str("||");
+
+    let mut chars = piece.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            // Already escaped, so leave it alone.
+            ('\\', Some('|')) => {
+                output.push_str("\\|");
+                chars.next();
+            }
+            ('|', _) => output.push_str("\\|"),
+            // A trailing backslash would escape the closing tag.
+            ('\\', None) => output.push_str("\\\\"),
+            _ => output.push(c),
+        }
+    }
+
+    output.push_str("||");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(input: &str, granularity: Granularity) -> String {
+        match spoiler(input.to_string(), granularity) {
+            Ok(Response::Spoiler { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn words_are_hidden_separately() {
+        assert_eq!(
+            output("hello  big\nworld", Granularity::Word),
+            "||hello||  ||big||\n||world||"
+        );
+    }
+
+    #[test]
+    fn characters_are_hidden_separately() {
+        assert_eq!(output("a b", Granularity::Char), "||a|| ||b||");
+        assert_eq!(output("👍🏽é", Granularity::Char), "||👍🏽||||é||");
+    }
+
+    #[test]
+    fn pipes_and_backslashes_are_escaped() {
+        assert_eq!(output("a|b c\\", Granularity::Word), "||a\\|b|| ||c\\\\||");
+        assert_eq!(output("a\\|b", Granularity::Word), "||a\\|b||");
+        assert_eq!(output("\\|a|", Granularity::Char), "||\\|||||a||||\\|||");
+    }
+
+    #[test]
+    fn markup_is_hidden_in_one_piece() {
+        assert_eq!(
+            output("hi `a b` <@123>", Granularity::Char),
+            "||h||||i|| ||`a b`|| ||<@123>||"
+        );
+    }
+
+    #[test]
+    fn long_output_is_rejected() {
+        let result = spoiler("a".repeat(500), Granularity::Char);
+
+        assert!(matches!(result, Err(CommandError::TooLong { .. })));
+    }
+}
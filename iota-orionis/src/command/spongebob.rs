@@ -1,28 +1,44 @@
 //! Convert text to Spongebob-case text.
 
-use super::Response;
+use rand::Rng;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{rng, Response};
 
 #[instrument]
-pub fn spongebob(input: String) -> Response {
-    let (_, spongebobified) =
-        input
-            .chars()
-            .fold((false, String::new()), |(upper, mut output), next_char| {
-                let next_upper = if next_char.is_alphanumeric() {
-                    if upper {
-                        output.push(next_char.to_ascii_uppercase());
-                    } else {
-                        output.push(next_char.to_ascii_lowercase());
-                    }
-
-                    !upper
-                } else {
-                    output.push(next_char);
-                    upper
-                };
-
-                (next_upper, output)
-            });
+pub fn spongebob(input: String, random: bool, seed: Option<u64>) -> Response {
+    let mut rng = rng(seed);
+
+    // The cases chosen for the last two graphemes that have case, so that
+    // random alternation can avoid long runs of the same case.
+    let mut previous: [Option<bool>; 2] = [None, None];
+
+    let spongebobified = input
+        .graphemes(true)
+        .map(|grapheme| {
+            // Only graphemes that actually have case count towards the
+            // alternation, so that digits, punctuation and letters from
+            // caseless scripts don't throw it off.
+            if !has_case(grapheme) {
+                return grapheme.to_string();
+            }
+
+            let upper = match previous {
+                [Some(first), Some(second)] if random && first == second => !second,
+                _ if random => rng.gen_bool(0.5),
+                [_, Some(last)] => !last,
+                _ => false,
+            };
+
+            previous = [previous[1], Some(upper)];
+
+            if upper {
+                grapheme.to_uppercase()
+            } else {
+                grapheme.to_lowercase()
+            }
+        })
+        .collect::<String>();
 
     let response = Response::Spongebob {
         output: spongebobified,
@@ -32,3 +48,46 @@ pub fn spongebob(input: String) -> Response {
 
     response
 }
+
+/// Determine whether a grapheme has case, based on whether its first character
+/// has different uppercase and lowercase forms.
+fn has_case(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .next()
+        .filter(|c| c.to_uppercase().ne(c.to_lowercase()))
+        .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(input: &str, random: bool) -> String {
+        match spongebob(input.to_string(), random, Some(1)) {
+            Response::Spongebob { output } => output,
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+
+    #[test]
+    fn emoji_sequences_are_kept_whole() {
+        for emoji in ["👨‍👩‍👧‍👦", "🇬🇧", "👍🏽", "1️⃣"] {
+            let input = format!("a{}b", emoji);
+
+            assert_eq!(output(&input, false), format!("a{}B", emoji));
+            assert!(output(&input, true).contains(emoji));
+        }
+    }
+
+    #[test]
+    fn combining_characters_stay_with_their_letter() {
+        assert_eq!(output("e\u{301}e\u{301}e", false), "e\u{301}E\u{301}e");
+        assert_eq!(output("éé", false), "éÉ");
+    }
+
+    #[test]
+    fn graphemes_without_case_are_skipped() {
+        assert_eq!(output("a👍🏽b 1 c", false), "a👍🏽B 1 c");
+    }
+}
@@ -0,0 +1,177 @@
+//! Correct a message with a sed-style substitution, like `s/teh/the/g`.
+
+use regex::RegexBuilder;
+
+use super::{check_length, CommandError, Response};
+
+/// The maximum size in bytes of a compiled pattern, which stops patterns like
+/// `(a{100}){100}` from using lots of memory. The same limit applies to the
+/// cache of the lazy DFA used to search, which grows as the input is matched.
+/// The regex engine already runs in linear time, so matching itself doesn't
+/// need a limit.
+const SIZE_LIMIT: usize = 1 << 20;
+
+/// The deepest that groups and repetitions can be nested in a pattern, which
+/// stops deeply nested patterns from overflowing the stack while parsing.
+const NEST_LIMIT: u32 = 50;
+
+#[instrument]
+pub fn substitute(
+    input: String,
+    pattern: String,
+    replacement: String,
+    global: bool,
+    ignore_case: bool,
+) -> Result<Response, CommandError> {
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(ignore_case)
+        .size_limit(SIZE_LIMIT)
+        .dfa_size_limit(SIZE_LIMIT)
+        .nest_limit(NEST_LIMIT)
+        .build()
+        .map_err(|err| {
+            warn!("pattern is not a valid regex: {}", err);
+
+            CommandError::InvalidRegex {
+                pattern: pattern.clone(),
+            }
+        })?;
+
+    if !regex.is_match(&input) {
+        warn!("pattern does not match input");
+
+        return Err(CommandError::NoMatch { pattern });
+    }
+
+    let replacement = expand_replacement(&replacement);
+    let limit = if global { 0 } else { 1 };
+    let output = regex.replacen(&input, limit, &*replacement).into_owned();
+
+    let response = Response::Substitute {
+        output: check_length(output)?,
+    };
+
+    debug!(?response);
+
+    Ok(response)
+}
+
+/// Convert a replacement written for sed, where `&` is the whole match and `\1`
+/// to `\9` are capture groups, to the syntax used by [`regex`].
+///
+/// A backslash before any other character makes it literal, so `\&` is a plain
+/// `&`.
+fn expand_replacement(replacement: &str) -> String {
+    let mut expanded = String::new();
+    let mut chars = replacement.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => expanded.push_str("${0}"),
+            '$' => expanded.push_str("$$"),
+            '\\' => match chars.next() {
+                Some(digit @ '0'..='9') => {
+                    expanded.push_str("${");
+                    expanded.push(digit);
+                    expanded.push('}');
+                }
+                Some('n') => expanded.push('\n'),
+                Some('$') => expanded.push_str("$$"),
+                Some(next) => expanded.push(next),
+                None => expanded.push('\\'),
+            },
+            _ => expanded.push(c),
+        }
+    }
+
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn substituted(
+        input: &str,
+        pattern: &str,
+        replacement: &str,
+        global: bool,
+        ignore_case: bool,
+    ) -> Result<String, CommandError> {
+        substitute(
+            input.to_string(),
+            pattern.to_string(),
+            replacement.to_string(),
+            global,
+            ignore_case,
+        )
+        .map(|response| match response {
+            Response::Substitute { output } => output,
+            response => panic!("unexpected response {:?}", response),
+        })
+    }
+
+    #[test]
+    fn flags_are_applied() {
+        let input = "teh cat and Teh dog";
+
+        assert_eq!(
+            substituted(input, "teh", "the", false, false).unwrap(),
+            "the cat and Teh dog"
+        );
+        assert_eq!(
+            substituted(input, "teh", "the", true, false).unwrap(),
+            "the cat and Teh dog"
+        );
+        assert_eq!(
+            substituted(input, "teh", "the", true, true).unwrap(),
+            "the cat and the dog"
+        );
+    }
+
+    #[test]
+    fn sed_replacements_are_expanded() {
+        let cases = [
+            (r"(\w+) (\w+)", r"\2 \1", "world hello"),
+            ("hello", "[&]", "[hello] world"),
+            ("hello", r"\&", "& world"),
+            ("hello", "$1", "$1 world"),
+            ("hello", r"\$0", "$0 world"),
+            (" ", r"\n", "hello\nworld"),
+        ];
+
+        for (pattern, replacement, expected) in cases {
+            assert_eq!(
+                substituted("hello world", pattern, replacement, false, false).unwrap(),
+                expected,
+                "s/{}/{}/",
+                pattern,
+                replacement
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let nested = format!("{}a{}", "(".repeat(100), ")".repeat(100));
+
+        for pattern in ["(", "a{", r"(a{100}){100}{100}", &nested] {
+            assert!(
+                matches!(
+                    substituted("a", pattern, "b", false, false),
+                    Err(CommandError::InvalidRegex { .. })
+                ),
+                "{:?} was accepted",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn patterns_that_do_not_match_are_rejected() {
+        assert!(matches!(
+            substituted("hello", "bye", "hi", false, false),
+            Err(CommandError::NoMatch { .. })
+        ));
+    }
+}
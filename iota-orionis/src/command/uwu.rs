@@ -0,0 +1,181 @@
+//! Convert text to uwu-speak.
+
+use rand::{seq::SliceRandom, Rng};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{
+    markup::{self, Segment},
+    rng, Intensity, Response,
+};
+
+/// Faces that can be added after the end of a sentence.
+const FACES: &[&str] = &["uwu", "owo", "UwU", "OwO", ">w<", "^w^", ":3", "x3", "rawr"];
+
+/// The chance of stuttering on each word, and the chance of adding a face after
+/// each sentence, for each intensity.
+fn chances(intensity: Intensity) -> (f64, f64) {
+    match intensity {
+        Intensity::Mini => (0.05, 0.25),
+        Intensity::Normal => (0.15, 0.5),
+        Intensity::Max => (0.35, 1.0),
+    }
+}
+
+#[instrument]
+pub fn uwu(input: String, intensity: Intensity, seed: Option<u64>) -> Response {
+    let mut rng = rng(seed);
+
+    let response = Response::Uwu {
+        output: uwuify(&input, intensity, &mut rng),
+    };
+
+    debug!(?response);
+
+    response
+}
+
+/// Apply the substitutions, stutters and faces to the input, leaving Discord
+/// markup alone.
+fn uwuify<R: Rng>(input: &str, intensity: Intensity, rng: &mut R) -> String {
+    let (stutter_chance, face_chance) = chances(intensity);
+
+    let mut output = String::new();
+
+    for segment in markup::segments(input) {
+        let text = match segment {
+            Segment::Text(text) => text,
+            Segment::Verbatim(verbatim) => {
+                output.push_str(verbatim);
+                continue;
+            }
+        };
+
+        let mut words = text.split_word_bounds().peekable();
+
+        while let Some(word) = words.next() {
+            if word.starts_with(char::is_alphabetic) {
+                let word = substitute(word);
+
+                if rng.gen_bool(stutter_chance) {
+                    if let Some(first) = word.chars().next() {
+                        output.push(first);
+                        output.push('-');
+                    }
+                }
+
+                output.push_str(&word);
+            } else {
+                output.push_str(word);
+            }
+
+            // The end of a sentence is some ending punctuation followed by
+            // either whitespace or the end of the text.
+            let sentence_end = matches!(word, "." | "!" | "?")
+                && !matches!(words.peek(), Some(next) if !next.starts_with(char::is_whitespace));
+
+            if sentence_end && rng.gen_bool(face_chance) {
+                if let Some(face) = FACES.choose(rng) {
+                    output.push(' ');
+                    output.push_str(face);
+                }
+            }
+        }
+    }
+
+    // Text without any ending punctuation still deserves a face at the end.
+    let ends_sentence = output.trim_end().ends_with(['.', '!', '?']);
+
+    if !output.trim().is_empty() && !ends_sentence && rng.gen_bool(face_chance) {
+        if let Some(face) = FACES.choose(rng) {
+            output.push(' ');
+            output.push_str(face);
+        }
+    }
+
+    output
+}
+
+/// Apply the uwu letter substitutions to a single word: `r` and `l` become `w`,
+/// `n` followed by a vowel gains a `y`, and a final `ove` becomes `uv`.
+fn substitute(word: &str) -> String {
+    let word = match word.strip_suffix("ove") {
+        Some(stem) => format!("{}uv", stem),
+        None => word.to_string(),
+    };
+
+    let mut chars = word.chars().peekable();
+    let mut output = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            'r' | 'l' => output.push('w'),
+            'R' | 'L' => output.push('W'),
+            'n' | 'N' => {
+                output.push(c);
+
+                if let Some(&next) = chars.peek() {
+                    if matches!(next.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u') {
+                        output.push(if next.is_uppercase() { 'Y' } else { 'y' });
+                    }
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(input: &str, intensity: Intensity, seed: u64) -> String {
+        match uwu(input.to_string(), intensity, Some(seed)) {
+            Response::Uwu { output } => output,
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+
+    #[test]
+    fn letters_are_substituted() {
+        assert_eq!(substitute("Really"), "Weawwy");
+        assert_eq!(substitute("love"), "wuv");
+        assert_eq!(substitute("nice"), "nyice");
+        assert_eq!(substitute("NO"), "NYO");
+    }
+
+    #[test]
+    fn same_seed_gives_same_output() {
+        let input = "Hello there. I really love this!";
+
+        for seed in 0..10 {
+            assert_eq!(
+                output(input, Intensity::Max, seed),
+                output(input, Intensity::Max, seed)
+            );
+        }
+    }
+
+    #[test]
+    fn every_sentence_gets_a_face_at_max_intensity() {
+        let output = output("Hi. Bye", Intensity::Max, 1);
+        let faces = output
+            .split_whitespace()
+            .filter(|word| FACES.contains(word))
+            .count();
+
+        assert_eq!(faces, 2, "{:?}", output);
+    }
+
+    #[test]
+    fn markup_is_left_alone() {
+        let input = "look at `really` <@123> https://example.com/lol";
+
+        for seed in 0..10 {
+            let output = output(input, Intensity::Max, seed);
+
+            assert!(output.contains("`really` <@123> https://example.com/lol"));
+        }
+    }
+}
@@ -1,6 +1,8 @@
 //! Convert text to vaporwave (fullwidth) text.
 
-use super::{CommandError, Response};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{is_emoji_sequence, CommandError, Response};
 
 #[instrument]
 pub fn wavy(input: String) -> Result<Response, CommandError> {
@@ -17,20 +19,53 @@ pub fn wavy(input: String) -> Result<Response, CommandError> {
 
 fn wavify(input: String) -> Result<String, CommandError> {
     input
-        .chars()
-        .map(|c| {
-            let val = c as u32;
-            match val {
-                0x0020 => std::char::from_u32(0x3000).ok_or_else(|| {
-                    CommandError::Internal("Invalid fullwidth space character".to_string())
-                }),
-                0x0021..=0x007e => std::char::from_u32(val + 0xfee0).ok_or_else(|| {
-                    CommandError::Internal(
-                        "fullwidth character equivalent should be valid".to_string(),
-                    )
-                }),
-                _ => Ok(c),
+        .graphemes(true)
+        .map(|grapheme| {
+            // Keycap sequences like 1️⃣ are made from ASCII characters too, but
+            // converting them would break the emoji, so leave them alone.
+            if is_emoji_sequence(grapheme) {
+                return Ok(grapheme.to_string());
             }
+
+            grapheme
+                .chars()
+                .map(|c| {
+                    let val = c as u32;
+                    match val {
+                        0x0020 => std::char::from_u32(0x3000).ok_or_else(|| {
+                            CommandError::Internal("Invalid fullwidth space character".to_string())
+                        }),
+                        0x0021..=0x007e => std::char::from_u32(val + 0xfee0).ok_or_else(|| {
+                            CommandError::Internal(
+                                "fullwidth character equivalent should be valid".to_string(),
+                            )
+                        }),
+                        _ => Ok(c),
+                    }
+                })
+                .collect::<Result<String, CommandError>>()
         })
         .collect::<Result<String, CommandError>>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emoji_sequences_are_left_alone() {
+        for emoji in ["👨‍👩‍👧‍👦", "🇬🇧", "👍🏽", "1️⃣", "#️⃣"] {
+            let input = format!("a{}b", emoji);
+
+            assert_eq!(wavify(input).unwrap(), format!("ａ{}ｂ", emoji));
+        }
+    }
+
+    #[test]
+    fn combining_characters_stay_with_their_letter() {
+        assert_eq!(
+            wavify("e\u{301} é".to_string()).unwrap(),
+            "ｅ\u{301}\u{3000}é"
+        );
+    }
+}
@@ -1,36 +1,261 @@
 //! Convert text to Zalgo text.
 
 use rand::Rng;
+use unicode_segmentation::UnicodeSegmentation;
 
-use super::Response;
+use std::{ops::RangeInclusive, str::FromStr};
+
+use super::{rng, CommandError, Intensity, Response};
+
+/// Where combining characters are placed relative to the character they
+/// decorate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Combining characters that stack above the character.
+    Above,
+    /// Combining characters that overlay the character.
+    Through,
+    /// Combining characters that stack below the character.
+    Below,
+}
+
+impl Direction {
+    /// All the directions, in the order they are applied.
+    pub const ALL: [Direction; 3] = [Direction::Above, Direction::Through, Direction::Below];
+
+    /// The combining characters that can be used in this direction.
+    fn combiners(self) -> &'static [char] {
+        match self {
+            Direction::Above => ABOVE,
+            Direction::Through => THROUGH,
+            Direction::Below => BELOW,
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = CommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_lowercase() {
+            "above" | "up" => Ok(Direction::Above),
+            "through" | "middle" => Ok(Direction::Through),
+            "below" | "down" => Ok(Direction::Below),
+            _ => Err(CommandError::InvalidArgument {
+                argument: "direction".to_string(),
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[rustfmt::skip]
+const ABOVE: &[char] = &[
+    '\u{0300}', '\u{0301}', '\u{0302}', '\u{0303}', '\u{0304}', '\u{0305}', '\u{0306}', '\u{0307}',
+    '\u{0308}', '\u{0309}', '\u{030a}', '\u{030b}', '\u{030c}', '\u{030d}', '\u{030e}', '\u{030f}',
+    '\u{0310}', '\u{0311}', '\u{0312}', '\u{0313}', '\u{0314}', '\u{031a}', '\u{033d}', '\u{033e}',
+    '\u{033f}', '\u{0342}', '\u{0343}', '\u{0344}', '\u{0346}', '\u{034a}', '\u{034b}', '\u{034c}',
+    '\u{0350}', '\u{0351}', '\u{0352}', '\u{0357}', '\u{035b}', '\u{0363}', '\u{0364}', '\u{0365}',
+    '\u{0366}', '\u{0367}', '\u{0368}', '\u{0369}', '\u{036a}', '\u{036b}', '\u{036c}', '\u{036d}',
+    '\u{036e}', '\u{036f}',
+];
+
+#[rustfmt::skip]
+const THROUGH: &[char] = &[
+    '\u{0315}', '\u{031b}', '\u{0334}', '\u{0335}', '\u{0336}', '\u{0337}', '\u{0338}', '\u{0340}',
+    '\u{0341}', '\u{0358}', '\u{035c}', '\u{035d}', '\u{035e}', '\u{035f}', '\u{0360}', '\u{0361}',
+    '\u{0362}',
+];
+
+#[rustfmt::skip]
+const BELOW: &[char] = &[
+    '\u{0316}', '\u{0317}', '\u{0318}', '\u{0319}', '\u{031c}', '\u{031d}', '\u{031e}', '\u{031f}',
+    '\u{0320}', '\u{0321}', '\u{0322}', '\u{0323}', '\u{0324}', '\u{0325}', '\u{0326}', '\u{0327}',
+    '\u{0328}', '\u{0329}', '\u{032a}', '\u{032b}', '\u{032c}', '\u{032d}', '\u{032e}', '\u{032f}',
+    '\u{0330}', '\u{0331}', '\u{0332}', '\u{0333}', '\u{0339}', '\u{033a}', '\u{033b}', '\u{033c}',
+    '\u{0345}', '\u{0347}', '\u{0348}', '\u{0349}', '\u{034d}', '\u{034e}', '\u{0353}', '\u{0354}',
+    '\u{0355}', '\u{0356}', '\u{0359}', '\u{035a}',
+];
+
+/// Determine whether a character is one of the combining characters that
+/// [`zalgo()`] adds.
+pub(super) fn is_combiner(c: char) -> bool {
+    ABOVE.contains(&c) || THROUGH.contains(&c) || BELOW.contains(&c)
+}
 
 #[instrument]
-pub fn zalgo(input: String, max_chars: Option<usize>) -> Response {
-    let per_char = max_chars
-        .map(|max_chars| (max_chars - input.len()) / input.len())
-        .map(|per_char| per_char.min(10))
-        .unwrap_or_else(|| 10);
+pub fn zalgo(
+    input: String,
+    max_chars: Option<usize>,
+    intensity: Intensity,
+    directions: Vec<Direction>,
+    seed: Option<u64>,
+) -> Result<Response, CommandError> {
+    let length = input.chars().count();
+    let graphemes = input.graphemes(true).count();
+
+    // There's nothing to decorate in an empty input, so there's no need to
+    // work out how many combining characters to add.
+    if graphemes == 0 {
+        return Ok(Response::Zalgo {
+            output: String::new(),
+        });
+    }
+
+    // Share out whatever room is left over after the input itself between
+    // each of its graphemes. If the input doesn't fit at all, it can't be
+    // converted without going over the limit.
+    let per_grapheme = match max_chars {
+        Some(max_chars) if max_chars < length => {
+            warn!("input is longer than max_chars");
+
+            return Err(CommandError::TooLong { length, max_chars });
+        }
+        Some(max_chars) => (max_chars - length) / graphemes,
+        None => usize::MAX,
+    };
+
+    // Use all the directions if none were specified.
+    let directions = if directions.is_empty() {
+        Direction::ALL.to_vec()
+    } else {
+        directions
+    };
+
+    // Using the same seed must always produce the same output, so only use
+    // entropy if no seed was given.
+    let mut rng = rng(seed);
 
     let response = Response::Zalgo {
-        output: zalgify(input, per_char),
+        output: zalgify(&input, per_grapheme, intensity, &directions, &mut rng),
     };
 
     debug!(?response);
 
-    response
+    Ok(response)
 }
 
-fn zalgify(input: String, per_char: usize) -> String {
+/// The range of the number of combining characters to add to each grapheme in
+/// each direction, for each intensity.
+fn range(intensity: Intensity) -> RangeInclusive<usize> {
+    match intensity {
+        Intensity::Mini => 0..=2,
+        Intensity::Normal => 1..=5,
+        Intensity::Max => 3..=16,
+    }
+}
+
+/// Add combining characters after every grapheme in the input, adding at most
+/// `per_grapheme` after each one.
+fn zalgify<R: Rng>(
+    input: &str,
+    per_grapheme: usize,
+    intensity: Intensity,
+    directions: &[Direction],
+    rng: &mut R,
+) -> String {
+    append_combiners(input, |decorated| {
+        let mut budget = per_grapheme;
+
+        for direction in directions {
+            let combiners = direction.combiners();
+            let count = rng.gen_range(range(intensity)).min(budget);
+            budget -= count;
+
+            decorated.extend((0..count).map(|_| combiners[rng.gen_range(0..combiners.len())]));
+        }
+    })
+}
+
+/// Add combining characters after every grapheme in the input, by calling
+/// `combine` to push them onto the end of each grapheme in turn.
+///
+/// Combining characters are added after whole graphemes rather than individual
+/// characters, so that they don't end up in the middle of sequences like flags
+/// or emojis with skin tone modifiers. Control characters such as line breaks
+/// are left alone, since there's nothing for combining characters to attach to.
+pub(super) fn append_combiners<F: FnMut(&mut String)>(input: &str, mut combine: F) -> String {
     input
-        .chars()
-        .flat_map(|c| {
-            let mut rng = rand::thread_rng();
-            let combiners = (0..per_char).map(|_| {
-                let val = rng.gen_range(0x300..0x36f);
-                unsafe { std::char::from_u32_unchecked(val) }
-            });
-
-            std::iter::once(c).chain(combiners).collect::<Vec<char>>()
+        .graphemes(true)
+        .map(|grapheme| {
+            let mut decorated = grapheme.to_string();
+
+            if !grapheme.chars().all(char::is_control) {
+                combine(&mut decorated);
+            }
+
+            decorated
         })
         .collect::<String>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUTS: &[&str] = &[
+        "",
+        "a",
+        "hello world",
+        "👨‍👩‍👧‍👦 family",
+        "🇬🇧🇫🇷",
+        "👍🏽👋🏿",
+        "1️⃣ #️⃣",
+        "e\u{301}\u{327} café",
+    ];
+
+    fn output(input: &str, max_chars: Option<usize>, intensity: Intensity) -> String {
+        match zalgo(input.to_string(), max_chars, intensity, Vec::new(), Some(1)) {
+            Ok(Response::Zalgo { output }) => output,
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn combiners_are_added_after_whole_graphemes() {
+        for input in INPUTS {
+            let original = input.graphemes(true).collect::<Vec<_>>();
+            let output = output(input, None, Intensity::Max);
+            let decorated = output.graphemes(true).collect::<Vec<_>>();
+
+            assert_eq!(original.len(), decorated.len());
+
+            for (original, decorated) in original.iter().zip(decorated) {
+                assert!(decorated.starts_with(original));
+                assert!(decorated[original.len()..].chars().all(is_combiner));
+            }
+        }
+    }
+
+    #[test]
+    fn output_fits_in_max_chars() {
+        for input in INPUTS {
+            let length = input.chars().count();
+
+            for max_chars in [length, length + 1, length + 20, 200] {
+                let output = output(input, Some(max_chars), Intensity::Max);
+
+                assert!(output.chars().count() <= max_chars);
+            }
+        }
+    }
+
+    #[test]
+    fn input_longer_than_max_chars_is_rejected() {
+        let result = zalgo(
+            "hello".to_string(),
+            Some(3),
+            Intensity::Normal,
+            Vec::new(),
+            None,
+        );
+
+        assert!(matches!(
+            result,
+            Err(CommandError::TooLong {
+                length: 5,
+                max_chars: 3
+            })
+        ));
+    }
+}